@@ -0,0 +1,97 @@
+//! Throughput benchmarks for the iterators.
+//!
+//! Run with `cargo bench`. This is a plain timing loop, so that it doesn't
+//! need any extra dependencies.
+
+use magnesium::*;
+use std::{fmt::Write, hint::black_box, time::Instant};
+
+/// Makes a registry style document with `count` enum entries.
+fn sample(count: usize) -> String {
+  let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+  xml.push_str("<registry>\n  <!-- generated sample -->\n");
+  xml.push_str("  <enums namespace=\"Graphics\" group=\"Polygon\">\n");
+  for i in 0..count {
+    writeln!(
+      xml,
+      "    <enum value=\"0x{:04X}\" name=\"GRAPHICS_ENUM_{}\" \
+       comment=\"#{}\"/>",
+      i, i, i
+    )
+    .unwrap();
+    if i % 16 == 0 {
+      writeln!(xml, "    <type>typedef int <name>Type{}</name>;</type>", i)
+        .unwrap();
+    }
+  }
+  xml.push_str("  </enums>\n</registry>\n");
+  xml
+}
+
+/// Makes a document of `count` paragraphs of mostly non-ASCII text.
+fn text_sample(count: usize) -> String {
+  let mut xml = String::from("<doc>\n");
+  for i in 0..count {
+    writeln!(
+      xml,
+      "  <p n=\"{}\">Ünïcödé text — with &amp; entities, and ‘quotes’ \
+       that goes on for a while → {}</p>",
+      i, i
+    )
+    .unwrap();
+  }
+  xml.push_str("</doc>\n");
+  xml
+}
+
+/// Runs `f` a few times and prints the throughput over `bytes` of input.
+fn bench(name: &str, bytes: usize, mut f: impl FnMut() -> usize) {
+  // warm up
+  black_box(f());
+  let runs = 20;
+  let start = Instant::now();
+  for _ in 0..runs {
+    black_box(f());
+  }
+  let secs = start.elapsed().as_secs_f64();
+  let mb_per_sec = (bytes * runs) as f64 / secs / 1_000_000.0;
+  println!("{:<24} {:>10.1} MB/s", name, mb_per_sec);
+}
+
+fn main() {
+  let xml = sample(50_000);
+  println!("sample is {} bytes", xml.len());
+  if cfg!(feature = "memchr") {
+    println!("scanning with the `memchr` feature");
+  }
+
+  bench("elements", xml.len(), || ElementIterator::new(&xml).count());
+
+  bench("elements + attributes", xml.len(), || {
+    ElementIterator::new(&xml)
+      .map(|el| match el {
+        XmlElement::StartTag { attrs, .. }
+        | XmlElement::EmptyTag { attrs, .. } => {
+          TagAttributeIterator::new(attrs).count()
+        }
+        _ => 0,
+      })
+      .sum()
+  });
+
+  bench("attributes find_by_key", xml.len(), || {
+    ElementIterator::new(&xml)
+      .filter_map(|el| match el {
+        XmlElement::EmptyTag { attrs, .. } => {
+          TagAttributeIterator::new(attrs).find_by_key("comment")
+        }
+        _ => None,
+      })
+      .count()
+  });
+
+  let text = text_sample(50_000);
+  println!("text sample is {} bytes", text.len());
+
+  bench("text elements", text.len(), || ElementIterator::new(&text).count());
+}
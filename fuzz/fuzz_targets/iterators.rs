@@ -0,0 +1,17 @@
+//! Run with `cargo fuzz run iterators` from the crate's root folder.
+//!
+//! The iterators promise to never panic on any `&str` input, so this runs them
+//! (and the things built on them) over arbitrary UTF-8 data.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use magnesium::*;
+
+include!("../../tests/shared/exercise.rs");
+
+fuzz_target!(|data: &[u8]| {
+  if let Ok(xml) = core::str::from_utf8(data) {
+    exercise(xml);
+  }
+});
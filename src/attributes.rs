@@ -1,4 +1,5 @@
 use super::*;
+use core::ops::Range;
 
 /// The output of a [`TagAttributeIterator`].
 ///
@@ -13,57 +14,313 @@ pub struct TagAttribute<'s> {
   pub key: &'s str,
   pub value: &'s str,
 }
+impl<'s> TagAttribute<'s> {
+  /// The bytes of the value.
+  #[inline]
+  #[must_use]
+  pub fn value_bytes(&self) -> &'s [u8] {
+    self.value.as_bytes()
+  }
+
+  /// Splits the value into its whitespace separated tokens.
+  ///
+  /// This is for list valued attributes, such as `class="a b c"`. The value is
+  /// split on ASCII whitespace, and there are no empty tokens, so runs of
+  /// whitespace (and any at the ends) are skipped.
+  ///
+  /// ```rust
+  /// # use magnesium::TagAttribute;
+  /// let attr = TagAttribute { key: "class", value: " a  b\tc " };
+  /// assert!(attr.value_tokens().eq(["a", "b", "c"]));
+  /// ```
+  #[inline]
+  #[must_use = "iterators are lazy and do nothing unless consumed"]
+  pub fn value_tokens(&self) -> impl Iterator<Item = &'s str> + Clone {
+    self.value.split_ascii_whitespace()
+  }
+
+  /// If the value is exactly one of the `allowed` strings.
+  ///
+  /// ```rust
+  /// # use magnesium::TagAttribute;
+  /// let attr = TagAttribute { key: "optional", value: "true" };
+  /// assert!(attr.value_in(&["true", "false"]));
+  /// assert!(!attr.value_in(&["yes", "no"]));
+  /// ```
+  #[inline]
+  #[must_use]
+  pub fn value_in(&self, allowed: &[&str]) -> bool {
+    allowed.contains(&self.value)
+  }
+
+  /// If this attribute declares a namespace (`xmlns` or `xmlns:prefix`).
+  ///
+  /// ```rust
+  /// # use magnesium::TagAttribute;
+  /// let attr = |key| TagAttribute { key, value: "urn:a" };
+  /// assert!(attr("xmlns").is_namespace_declaration());
+  /// assert!(attr("xmlns:x").is_namespace_declaration());
+  /// assert!(!attr("xmlnsx").is_namespace_declaration());
+  /// ```
+  #[inline]
+  #[must_use]
+  pub fn is_namespace_declaration(&self) -> bool {
+    self.key == "xmlns" || self.key.starts_with("xmlns:")
+  }
+
+  /// Gets the prefix that this attribute declares a namespace for.
+  ///
+  /// This is the `x` of `xmlns:x`. Attributes that declare the default
+  /// namespace (`xmlns`) or that aren't namespace declarations give `None`.
+  ///
+  /// ```rust
+  /// # use magnesium::TagAttribute;
+  /// assert_eq!(
+  ///   TagAttribute { key: "xmlns:x", value: "urn:a" }.namespace_prefix(),
+  ///   Some("x")
+  /// );
+  /// assert_eq!(
+  ///   TagAttribute { key: "xmlns", value: "urn:a" }.namespace_prefix(),
+  ///   None
+  /// );
+  /// ```
+  #[inline]
+  #[must_use]
+  pub fn namespace_prefix(&self) -> Option<&'s str> {
+    self.key.strip_prefix("xmlns:")
+  }
+
+  /// Decodes a hex string value into bytes, returning the count written.
+  ///
+  /// The value can optionally start with `0x`. After that it must be an even
+  /// number of hex digits (either case), each pair becoming one byte.
+  ///
+  /// ```rust
+  /// # use magnesium::TagAttribute;
+  /// let attr = TagAttribute { key: "bits", value: "0x01ff" };
+  /// let mut buf = [0_u8; 4];
+  /// assert_eq!(attr.decode_hex_into(&mut buf), Some(2));
+  /// assert_eq!(&buf[..2], &[0x01, 0xFF]);
+  /// ```
+  ///
+  /// ## Failure
+  /// * If the value has an odd number of digits or a non-hex character.
+  /// * If `out` is too small to hold all the bytes.
+  #[must_use]
+  pub fn decode_hex_into(&self, out: &mut [u8]) -> Option<usize> {
+    let digits = self.value.strip_prefix("0x").unwrap_or(self.value);
+    let pairs = digits.as_bytes().chunks_exact(2);
+    if !pairs.remainder().is_empty() || pairs.len() > out.len() {
+      return None;
+    }
+    let count = pairs.len();
+    for (pair, o) in pairs.zip(out.iter_mut()) {
+      let hi = (pair[0] as char).to_digit(16)?;
+      let lo = (pair[1] as char).to_digit(16)?;
+      *o = (hi << 4 | lo) as u8;
+    }
+    Some(count)
+  }
+
+  /// Decodes the entities of the value into `out`, returning the count of
+  /// bytes written.
+  ///
+  /// This decodes the same way as [`write_decoded`], but into a byte buffer so
+  /// that it works without `alloc`. The output is UTF-8.
+  ///
+  /// ```rust
+  /// # use magnesium::TagAttribute;
+  /// let attr = TagAttribute { key: "a", value: "&#65;&amp;B" };
+  /// let mut buf = [0_u8; 8];
+  /// assert_eq!(attr.decode_value_into(&mut buf), Ok(3));
+  /// assert_eq!(&buf[..3], b"A&B");
+  /// ```
+  ///
+  /// ## Failure
+  /// * Same as with [`write_decoded`]. If `out` is too small to hold all the
+  ///   output, the error kind is `DecodeErrorKind::Write`.
+  ///
+  /// Any output before the problem will already have been written to `out`.
+  pub fn decode_value_into(
+    &self, out: &mut [u8],
+  ) -> Result<usize, DecodeError> {
+    let mut writer = SliceWriter { out, len: 0 };
+    write_decoded(&mut writer, self.value)?;
+    Ok(writer.len)
+  }
+}
+
+/// Writes into a byte slice, failing if it fills up.
+struct SliceWriter<'o> {
+  out: &'o mut [u8],
+  len: usize,
+}
+impl core::fmt::Write for SliceWriter<'_> {
+  fn write_str(&mut self, s: &str) -> core::fmt::Result {
+    let dest =
+      self.out.get_mut(self.len..self.len + s.len()).ok_or(core::fmt::Error)?;
+    dest.copy_from_slice(s.as_bytes());
+    self.len += s.len();
+    Ok(())
+  }
+}
+
+/// Formats as `key="value"`, the way the attribute would appear in a tag.
+///
+/// The value is written as-is, without any encoding. If the value contains a
+/// `"` then it's quoted with `'` instead. If it contains both kinds of quote
+/// then it's quoted with `"`, and each `"` within it is written as `&quot;`.
+///
+/// ```rust
+/// # use magnesium::TagAttribute;
+/// let attr = TagAttribute { key: "a", value: "b" };
+/// assert_eq!(format!("{}", attr), r#"a="b""#);
+/// let attr = TagAttribute { key: "a", value: r#"b"c'd"# };
+/// assert_eq!(format!("{}", attr), r#"a="b&quot;c'd""#);
+/// ```
+impl core::fmt::Display for TagAttribute<'_> {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    let has_double = self.value.contains('"');
+    if has_double && !self.value.contains('\'') {
+      return write!(f, "{}='{}'", self.key, self.value);
+    }
+    write!(f, "{}=\"", self.key)?;
+    for (i, part) in self.value.split('"').enumerate() {
+      if i > 0 {
+        f.write_str("&quot;")?;
+      }
+      f.write_str(part)?;
+    }
+    f.write_str("\"")
+  }
+}
 
 /// Iterator to walk through a `Start` or `Empty` tag's attribute string.
 ///
 /// Supports both `'` and `"` quoting around the attribute values.
 ///
 /// The parsing is a little simplistic, and if the iterator gets confused by bad
-/// input it will just end the iteration.
+/// input it will just end the iteration. The iterator never panics, whatever
+/// the input `&str` is.
+///
+/// A `Default` iterator has no input, so it gives no attributes. See also
+/// [`empty`](Self::empty).
 #[derive(Debug, Clone, Default)]
 pub struct TagAttributeIterator<'s> {
   attrs: &'s str,
+  /// The full input, for computing byte offsets.
+  source: &'s str,
+  /// Allow attributes without a value.
+  lenient: bool,
 }
 impl<'s> TagAttributeIterator<'s> {
+  /// Makes an iterator that gives no attributes.
+  ///
+  /// This is the same as `TagAttributeIterator::default()`, and is handy as a
+  /// placeholder, such as for an element that isn't a tag.
+  ///
+  /// ```rust
+  /// # use magnesium::*;
+  /// assert_eq!(TagAttributeIterator::empty().next(), None);
+  /// ```
+  #[inline]
+  #[must_use]
+  pub fn empty() -> Self {
+    Self::default()
+  }
+
   /// Makes a new iterator over the attribute string.
   #[inline]
   #[must_use]
   pub fn new(attrs: &'s str) -> Self {
-    Self { attrs: attrs.trim() }
+    Self { attrs: attrs.trim(), source: attrs, lenient: false }
   }
 
-  /// Gets the `value` of the `key` given, if the key is present.
+  /// Makes a new iterator that also accepts attributes without a value.
+  ///
+  /// HTML style boolean attributes, such as `disabled` in
+  /// `<input disabled>`, don't have an `=` or a value at all. A normal
+  /// iterator ends the iteration when it sees one of these, but this iterator
+  /// gives it as an attribute with an empty `value` and keeps going. Other
+  /// attributes are parsed as normal.
   ///
   /// ```rust
-  /// # use magnesium::TagAttributeIterator;
-  /// let attrs = r#"namespace="Graphics" group="Polygon""#;
-  /// let iter = TagAttributeIterator::new(attrs);
-  /// assert_eq!(iter.find_by_key("namespace"), Some("Graphics"));
-  /// assert_eq!(iter.find_by_key("ferris"), None);
-  /// assert_eq!(iter.find_by_key("group"), Some("Polygon"));
+  /// # use magnesium::*;
+  /// let mut iter = TagAttributeIterator::new_lenient(r#"type="box" checked"#);
+  /// assert_eq!(iter.next(), Some(TagAttribute { key: "type", value: "box" }));
+  /// assert_eq!(iter.next(), Some(TagAttribute { key: "checked", value: "" }));
+  /// assert_eq!(iter.next(), None);
   /// ```
   #[inline]
   #[must_use]
-  pub fn find_by_key(&self, key: &str) -> Option<&'s str> {
-    self.clone().find(|ta| ta.key == key).map(|ta| ta.value)
+  pub fn new_lenient(attrs: &'s str) -> Self {
+    Self { lenient: true, ..Self::new(attrs) }
   }
-}
-impl<'s> Iterator for TagAttributeIterator<'s> {
-  type Item = TagAttribute<'s>;
 
+  /// Iterates the attributes along with their position within the tag.
+  ///
+  /// The index counts from 0 in source order. If malformed input ends the
+  /// iteration early, the attributes before that point keep their indexes.
+  ///
+  /// ```rust
+  /// # use magnesium::*;
+  /// let mut iter =
+  ///   TagAttributeIterator::new(r#"a="1" b="2""#).enumerate_attributes();
+  /// assert_eq!(iter.next(), Some((0, TagAttribute { key: "a", value: "1" })));
+  /// assert_eq!(iter.next(), Some((1, TagAttribute { key: "b", value: "2" })));
+  /// assert_eq!(iter.next(), None);
+  /// ```
   #[inline]
-  #[must_use]
-  fn next(&mut self) -> Option<Self::Item> {
+  #[must_use = "iterators are lazy and do nothing unless consumed"]
+  pub fn enumerate_attributes(self) -> core::iter::Enumerate<Self> {
+    self.enumerate()
+  }
+
+  /// Gets the next attribute, or an error if the input is malformed.
+  ///
+  /// Where `next` just ends the iteration when it gets confused by the input,
+  /// this gives an [`XmlError`] first. The error's `offset` is the byte
+  /// position of the bad attribute within the string this iterator was made
+  /// from. After an error, the iteration is over.
+  ///
+  /// ```rust
+  /// # use magnesium::*;
+  /// let mut iter = TagAttributeIterator::new(r#"a="1" b="2"#);
+  /// let a = TagAttribute { key: "a", value: "1" };
+  /// assert_eq!(iter.try_next(), Some(Ok(a)));
+  /// let err =
+  ///   XmlError { kind: XmlErrorKind::UnterminatedAttributeValue, offset: 6 };
+  /// assert_eq!(iter.try_next(), Some(Err(err)));
+  /// assert_eq!(iter.try_next(), None);
+  /// ```
+  #[inline]
+  pub fn try_next(&mut self) -> Option<Result<TagAttribute<'s>, XmlError>> {
     debug_assert_eq!(self.attrs, self.attrs.trim());
     if self.attrs.is_empty() {
       return None;
     }
+    let start = self.attrs;
     #[allow(clippy::never_loop)]
-    'clear_and_return_none: loop {
+    let kind = 'fail: loop {
+      if self.lenient {
+        let key_end = self
+          .attrs
+          .find(|c: char| c.is_whitespace() || c == '=')
+          .unwrap_or(self.attrs.len());
+        let (key, rest) = self.attrs.split_at(key_end);
+        let rest = rest.trim_start();
+        if !rest.starts_with('=') {
+          self.attrs = rest;
+          // an empty value that's still within the input, for the spans.
+          let value = &key[key.len()..];
+          return Some(Ok(TagAttribute { key, value }));
+        }
+      }
       // break on `=`
+      // whitespace (even newlines) is allowed on either side of the `=`
       let (key, rest) = match break_on_first_char(self.attrs, '=') {
-        Some((key, rest)) => (key, rest),
-        None => break 'clear_and_return_none,
+        Some((key, rest)) => (key.trim_end(), rest.trim_start()),
+        None => break 'fail XmlErrorKind::MalformedAttribute,
       };
       self.attrs = rest;
       // support both `"` and `'` since it's easy to do
@@ -72,18 +329,382 @@ impl<'s> Iterator for TagAttributeIterator<'s> {
           self.attrs = &self.attrs[1..];
           q
         }
-        _ => break 'clear_and_return_none,
+        _ => break 'fail XmlErrorKind::MalformedAttribute,
       };
       // break on the end of the quote
       let (value, rest) = match break_on_first_char(self.attrs, quote_marker) {
         Some((key, rest)) => (key, rest),
-        None => break 'clear_and_return_none,
+        None => break 'fail XmlErrorKind::UnterminatedAttributeValue,
       };
       self.attrs = rest.trim_start();
-      return Some(TagAttribute { key, value });
-    }
+      return Some(Ok(TagAttribute { key, value }));
+    };
     self.attrs = "";
-    None
+    let offset = byte_offset_of(start, self.source).unwrap_or(0);
+    Some(Err(XmlError { kind, offset }))
+  }
+
+  /// Makes a new iterator that decodes each value.
+  ///
+  /// The values are decoded like [`decode_xml_lossy`](crate::decode_xml_lossy),
+  /// so any entity that can't be decoded is kept as is. They only allocate
+  /// when they actually contain an `&`.
+  ///
+  /// ```rust
+  /// # use magnesium::TagAttributeIterator;
+  /// let attrs = r#"a="1 &lt; 2" b="R&D""#;
+  /// let mut iter = TagAttributeIterator::new_decoding(attrs);
+  /// assert_eq!(iter.next().unwrap().value, "1 < 2");
+  /// assert_eq!(iter.next().unwrap().value, "R&D");
+  /// ```
+  #[cfg(feature = "alloc")]
+  #[inline]
+  #[must_use]
+  pub fn new_decoding(attrs: &'s str) -> DecodingTagAttributeIterator<'s> {
+    DecodingTagAttributeIterator { iter: Self::new(attrs) }
+  }
+
+  /// Iterates the attributes along with where they are in the input.
+  ///
+  /// The spans are byte ranges within the string this iterator was made from,
+  /// so they can be used to point at an attribute in the source text.
+  ///
+  /// ```rust
+  /// # use magnesium::*;
+  /// let attrs = r#" a="1" bb='22'"#;
+  /// let mut iter = TagAttributeIterator::new(attrs).with_spans();
+  /// let _a = iter.next();
+  /// let (bb, span) = iter.next().unwrap();
+  /// assert_eq!(&attrs[span.key], bb.key);
+  /// assert_eq!(&attrs[span.value], bb.value);
+  /// ```
+  #[inline]
+  #[must_use]
+  pub fn with_spans(self) -> WithSpans<'s> {
+    WithSpans { iter: self }
+  }
+
+  /// Changes each attribute's value as it's iterated.
+  ///
+  /// Each value is passed to `f`, and the attribute is given with whatever
+  /// value `f` returns. The keys are unaffected. The new value must be
+  /// borrowed, such as a subslice of the old value or a `&'static str`.
+  ///
+  /// ```rust
+  /// # use magnesium::*;
+  /// let iter = TagAttributeIterator::new(r#"a=" x " b="y""#);
+  /// let mut iter = iter.map_values(str::trim);
+  /// assert_eq!(iter.next(), Some(TagAttribute { key: "a", value: "x" }));
+  /// assert_eq!(iter.next(), Some(TagAttribute { key: "b", value: "y" }));
+  /// ```
+  #[inline]
+  #[must_use]
+  pub fn map_values<F>(self, f: F) -> MapValues<'s, F>
+  where
+    F: FnMut(&'s str) -> &'s str,
+  {
+    MapValues { iter: self, f }
+  }
+
+  /// Checks if there's no more input for the iterator to process.
+  ///
+  /// Once this is `true`, `next` will always give `None`.
+  #[inline]
+  #[must_use]
+  pub fn is_done(&self) -> bool {
+    self.attrs.is_empty()
+  }
+
+  /// Gets the `value` of the `key` given, if the key is present.
+  ///
+  /// ```rust
+  /// # use magnesium::TagAttributeIterator;
+  /// let attrs = r#"namespace="Graphics" group="Polygon""#;
+  /// let iter = TagAttributeIterator::new(attrs);
+  /// assert_eq!(iter.find_by_key("namespace"), Some("Graphics"));
+  /// assert_eq!(iter.find_by_key("ferris"), None);
+  /// assert_eq!(iter.find_by_key("group"), Some("Polygon"));
+  /// ```
+  #[inline]
+  #[must_use]
+  pub fn find_by_key(&self, key: &str) -> Option<&'s str> {
+    self.clone().find(|ta| ta.key == key).map(|ta| ta.value)
+  }
+
+  /// Gets the `value` of the last attribute with the `key` given.
+  ///
+  /// This is like [`find_by_key`](Self::find_by_key), but if the key appears
+  /// more than once (which isn't valid XML) the last value wins.
+  ///
+  /// ```rust
+  /// # use magnesium::TagAttributeIterator;
+  /// let iter = TagAttributeIterator::new(r#"a="1" b="x" a="2""#);
+  /// assert_eq!(iter.find_by_key("a"), Some("1"));
+  /// assert_eq!(iter.find_by_key_last("a"), Some("2"));
+  /// assert_eq!(iter.find_by_key_last("c"), None);
+  /// ```
+  #[inline]
+  #[must_use]
+  pub fn find_by_key_last(&self, key: &str) -> Option<&'s str> {
+    self.clone().filter(|ta| ta.key == key).last().map(|ta| ta.value)
+  }
+
+  /// Gets the values of several keys in a single pass over the attributes.
+  ///
+  /// Each `out[i]` is set to the value of `keys[i]`, or `None` if the key isn't
+  /// present. Like with [`find_by_key`](Self::find_by_key), if a key appears
+  /// more than once the first value is used.
+  ///
+  /// ```rust
+  /// # use magnesium::TagAttributeIterator;
+  /// let iter = TagAttributeIterator::new(r#"name="A" value="0x1" alias="B""#);
+  /// let mut out = [None; 3];
+  /// iter.extract(&["value", "name", "comment"], &mut out);
+  /// assert_eq!(out, [Some("0x1"), Some("A"), None]);
+  /// ```
+  ///
+  /// ## Panics
+  /// If `keys` and `out` aren't the same length.
+  #[inline]
+  pub fn extract(&self, keys: &[&str], out: &mut [Option<&'s str>]) {
+    assert_eq!(keys.len(), out.len(), "`keys` and `out` lengths differ");
+    out.iter_mut().for_each(|o| *o = None);
+    for TagAttribute { key, value } in self.clone() {
+      for (k, o) in keys.iter().zip(out.iter_mut()) {
+        if *k == key && o.is_none() {
+          *o = Some(value);
+        }
+      }
+    }
+  }
+
+  /// Checks if the `key` given is present.
+  ///
+  /// ```rust
+  /// # use magnesium::TagAttributeIterator;
+  /// let iter = TagAttributeIterator::new(r#"name="A" deprecated="true""#);
+  /// assert!(iter.contains_key("deprecated"));
+  /// assert!(!iter.contains_key("alias"));
+  /// ```
+  #[inline]
+  #[must_use]
+  pub fn contains_key(&self, key: &str) -> bool {
+    self.clone().any(|ta| ta.key == key)
+  }
+
+  /// Gets the value of the `key` given as a `bool`.
+  ///
+  /// The value must be `true`, `false`, `1`, or `0`.
+  ///
+  /// ```rust
+  /// # use magnesium::TagAttributeIterator;
+  /// let iter = TagAttributeIterator::new(r#"a="true" b="0" c="yes""#);
+  /// assert_eq!(iter.get_bool("a"), Some(true));
+  /// assert_eq!(iter.get_bool("b"), Some(false));
+  /// assert_eq!(iter.get_bool("c"), None);
+  /// ```
+  ///
+  /// ## Failure
+  /// * If the key isn't present, or the value isn't one of the above.
+  #[inline]
+  #[must_use]
+  pub fn get_bool(&self, key: &str) -> Option<bool> {
+    match self.find_by_key(key)? {
+      "true" | "1" => Some(true),
+      "false" | "0" => Some(false),
+      _ => None,
+    }
+  }
+
+  /// Gets the value of the `key` given as an `f64`.
+  ///
+  /// ```rust
+  /// # use magnesium::TagAttributeIterator;
+  /// let iter = TagAttributeIterator::new(r#"x="1.5" y="-2e3""#);
+  /// assert_eq!(iter.get_f64("x"), Some(1.5));
+  /// assert_eq!(iter.get_f64("y"), Some(-2000.0));
+  /// ```
+  ///
+  /// ## Failure
+  /// * If the key isn't present, or the value doesn't parse as an `f64`.
+  #[inline]
+  #[must_use]
+  pub fn get_f64(&self, key: &str) -> Option<f64> {
+    self.find_by_key(key)?.parse().ok()
+  }
+
+  /// Gets the value of the `key` given as a `u64`.
+  ///
+  /// The value can be decimal, or hex if it starts with `0x`. It must be only
+  /// digits after that, so a sign (`+` or `-`) isn't allowed.
+  ///
+  /// ```rust
+  /// # use magnesium::TagAttributeIterator;
+  /// let iter = TagAttributeIterator::new(r#"count="12" value="0x00FF""#);
+  /// assert_eq!(iter.get_u64("count"), Some(12));
+  /// assert_eq!(iter.get_u64("value"), Some(255));
+  /// ```
+  ///
+  /// ## Failure
+  /// * If the key isn't present, or the value doesn't parse as a `u64`.
+  #[inline]
+  #[must_use]
+  pub fn get_u64(&self, key: &str) -> Option<u64> {
+    let value = self.find_by_key(key)?;
+    let (digits, radix) = match value.strip_prefix("0x") {
+      Some(hex) => (hex, 16),
+      None => (value, 10),
+    };
+    // `from_str_radix` allows a leading sign.
+    if digits.starts_with(['+', '-']) {
+      return None;
+    }
+    u64::from_str_radix(digits, radix).ok()
+  }
+
+  /// Collects all the attributes into a map from key to value.
+  ///
+  /// If a key appears more than once, the _last_ value for that key is what
+  /// ends up in the map.
+  ///
+  /// ```rust
+  /// # use magnesium::TagAttributeIterator;
+  /// let attrs = r#"name="GRAPHIC_POINTS" value="0x0000""#;
+  /// let map = TagAttributeIterator::new(attrs).to_btree_map();
+  /// assert_eq!(map.get("value"), Some(&"0x0000"));
+  /// ```
+  #[cfg(feature = "alloc")]
+  #[must_use]
+  pub fn to_btree_map(&self) -> BTreeMap<&'s str, &'s str> {
+    self.clone().map(|ta| (ta.key, ta.value)).collect()
+  }
+}
+impl<'s> Iterator for TagAttributeIterator<'s> {
+  type Item = TagAttribute<'s>;
+
+  #[inline]
+  fn next(&mut self) -> Option<Self::Item> {
+    self.try_next()?.ok()
   }
 }
 impl<'s> core::iter::FusedIterator for TagAttributeIterator<'s> {}
+
+/// Where an attribute's key and value are within an attribute string.
+///
+/// The value's range doesn't include the quotes around the value.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct AttrSpan {
+  /// The byte range of the key.
+  pub key: Range<usize>,
+  /// The byte range of the value.
+  pub value: Range<usize>,
+}
+
+/// Iterator for [`TagAttributeIterator::with_spans`].
+#[derive(Debug, Clone)]
+pub struct WithSpans<'s> {
+  iter: TagAttributeIterator<'s>,
+}
+impl<'s> Iterator for WithSpans<'s> {
+  type Item = (TagAttribute<'s>, AttrSpan);
+
+  #[inline]
+  fn next(&mut self) -> Option<Self::Item> {
+    let attr = self.iter.next()?;
+    let source = self.iter.source;
+    let range = |s: &str| {
+      let start = byte_offset_of(s, source).unwrap_or(0);
+      start..start + s.len()
+    };
+    let span = AttrSpan { key: range(attr.key), value: range(attr.value) };
+    Some((attr, span))
+  }
+}
+impl<'s> core::iter::FusedIterator for WithSpans<'s> {}
+
+/// Iterator for [`TagAttributeIterator::map_values`].
+#[derive(Debug, Clone)]
+pub struct MapValues<'s, F> {
+  iter: TagAttributeIterator<'s>,
+  f: F,
+}
+impl<'s, F> Iterator for MapValues<'s, F>
+where
+  F: FnMut(&'s str) -> &'s str,
+{
+  type Item = TagAttribute<'s>;
+
+  #[inline]
+  fn next(&mut self) -> Option<Self::Item> {
+    let TagAttribute { key, value } = self.iter.next()?;
+    Some(TagAttribute { key, value: (self.f)(value) })
+  }
+}
+impl<'s, F> core::iter::FusedIterator for MapValues<'s, F> where
+  F: FnMut(&'s str) -> &'s str
+{
+}
+
+/// A [`TagAttribute`] with its value decoded.
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[allow(missing_docs)]
+pub struct DecodedTagAttribute<'s> {
+  pub key: &'s str,
+  pub value: Cow<'s, str>,
+}
+
+/// Iterator for [`TagAttributeIterator::new_decoding`].
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, Default)]
+pub struct DecodingTagAttributeIterator<'s> {
+  iter: TagAttributeIterator<'s>,
+}
+#[cfg(feature = "alloc")]
+impl<'s> Iterator for DecodingTagAttributeIterator<'s> {
+  type Item = DecodedTagAttribute<'s>;
+
+  #[inline]
+  fn next(&mut self) -> Option<Self::Item> {
+    let TagAttribute { key, value } = self.iter.next()?;
+    let value = if value.contains('&') {
+      Cow::Owned(decode_xml_lossy(value))
+    } else {
+      Cow::Borrowed(value)
+    };
+    Some(DecodedTagAttribute { key, value })
+  }
+}
+#[cfg(feature = "alloc")]
+impl<'s> core::iter::FusedIterator for DecodingTagAttributeIterator<'s> {}
+
+/// Finds the value of `key` and the rest of the attributes after it.
+///
+/// This is for pulling several known keys out of an attribute string when you
+/// know what order they come in: each search picks up where the last one left
+/// off instead of starting over from the beginning.
+///
+/// ```rust
+/// # use magnesium::find_attr_and_rest;
+/// let attrs = r#"name="GRAPHIC_POINTS" value="0x0000" alias="POINTS""#;
+/// let (name, rest) = find_attr_and_rest(attrs, "name").unwrap();
+/// assert_eq!(name, "GRAPHIC_POINTS");
+/// let (value, rest) = find_attr_and_rest(rest, "value").unwrap();
+/// assert_eq!(value, "0x0000");
+/// assert_eq!(rest, r#"alias="POINTS""#);
+/// ```
+///
+/// ## Failure
+/// * If `key` isn't in the attributes (or comes after malformed input).
+#[must_use]
+pub fn find_attr_and_rest<'s>(
+  attrs: &'s str, key: &str,
+) -> Option<(&'s str, &'s str)> {
+  let mut iter = TagAttributeIterator::new(attrs);
+  while let Some(attr) = iter.next() {
+    if attr.key == key {
+      return Some((attr.value, iter.attrs));
+    }
+  }
+  None
+}
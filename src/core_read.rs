@@ -0,0 +1,141 @@
+use super::*;
+
+/// Parses XML that arrives a few bytes at a time, using a fixed buffer.
+///
+/// This is for when the input comes from something like a serial port and
+/// there's no allocator (or even a whole copy of the document) available.
+/// Bytes are pushed in with [`feed`](Self::feed), and each element is passed
+/// to a callback as soon as it's complete. Only the caller's `buffer` is used
+/// as storage, so any one element (tag, comment, run of text, etc.) must fit
+/// within the buffer.
+///
+/// Unlike with [`ElementIterator::new`], nothing is trimmed from the input. An
+/// XML declaration is given as a `ProcessingInstruction` with the target
+/// `xml`, and any whitespace outside of the root element is given as `Text`.
+/// Because text only ends at the next `<`, the final run of text is held back
+/// until [`finish`](Self::finish) is called.
+///
+/// The `offset` of any [`XmlError`] is counted from the start of the input.
+///
+/// ```rust
+/// # use magnesium::*;
+/// let mut buffer = [0_u8; 32];
+/// let mut parser = CoreReadParser::new(&mut buffer);
+/// let mut count = 0;
+/// for chunk in ["<a><b/", ">text</a>"].iter() {
+///   parser.feed(chunk.as_bytes(), |_el| count += 1).unwrap();
+/// }
+/// parser.finish(|_el| count += 1).unwrap();
+/// assert_eq!(count, 4);
+/// ```
+#[derive(Debug)]
+pub struct CoreReadParser<'b> {
+  buffer: &'b mut [u8],
+  /// How many bytes of `buffer` are in use.
+  len: usize,
+  /// How many bytes of input came before the start of `buffer`.
+  consumed: usize,
+}
+impl<'b> CoreReadParser<'b> {
+  /// Makes a new parser that uses `buffer` to hold partial elements.
+  #[inline]
+  #[must_use]
+  pub fn new(buffer: &'b mut [u8]) -> Self {
+    Self { buffer, len: 0, consumed: 0 }
+  }
+
+  /// Pushes more input into the parser.
+  ///
+  /// Each element that's completed by this input is passed to `f`, in order.
+  ///
+  /// ## Failure
+  /// * `BufferOverflow` if an element doesn't fit within the buffer.
+  /// * `InvalidUtf8` if the input isn't UTF-8.
+  /// * Any other problem that ends the iteration of an [`ElementIterator`],
+  ///   such as an `EmptyTagName`.
+  ///
+  /// After an error the parser's state is unspecified, and it should not be
+  /// used any more.
+  pub fn feed<F>(&mut self, mut bytes: &[u8], mut f: F) -> Result<(), XmlError>
+  where
+    F: FnMut(XmlElement<'_>),
+  {
+    while !bytes.is_empty() {
+      if self.len == self.buffer.len() {
+        return Err(self.error(0, XmlErrorKind::BufferOverflow));
+      }
+      let count = bytes.len().min(self.buffer.len() - self.len);
+      let (now, later) = bytes.split_at(count);
+      self.buffer[self.len..self.len + count].copy_from_slice(now);
+      self.len += count;
+      bytes = later;
+      self.process(&mut f, false)?;
+    }
+    Ok(())
+  }
+
+  /// Ends the input, passing any remaining element to `f`.
+  ///
+  /// ## Failure
+  /// * `UnexpectedEnd` if the input stopped in the middle of an element.
+  /// * `InvalidUtf8` if the input ended in the middle of a character.
+  /// * Same as with [`feed`](Self::feed).
+  pub fn finish<F>(&mut self, mut f: F) -> Result<(), XmlError>
+  where
+    F: FnMut(XmlElement<'_>),
+  {
+    self.process(&mut f, true)?;
+    if self.len == 0 {
+      Ok(())
+    } else {
+      Err(self.error(0, XmlErrorKind::UnexpectedEnd))
+    }
+  }
+
+  /// Gives all the complete elements in the buffer to `f` and then removes
+  /// them from the buffer.
+  fn process<F>(&mut self, f: &mut F, at_end: bool) -> Result<(), XmlError>
+  where
+    F: FnMut(XmlElement<'_>),
+  {
+    let bytes = &self.buffer[..self.len];
+    let text = match core::str::from_utf8(bytes) {
+      Ok(text) => text,
+      // a character can be split between two chunks of input.
+      Err(e) if e.error_len().is_none() && !at_end => {
+        core::str::from_utf8(&bytes[..e.valid_up_to()]).unwrap_or_default()
+      }
+      Err(e) => {
+        return Err(self.error(e.valid_up_to(), XmlErrorKind::InvalidUtf8))
+      }
+    };
+    let mut iter = ElementIterator::with_body(text, text).partial_input();
+    let mut done = 0;
+    let failure = loop {
+      match iter.parse_next() {
+        // the element isn't complete yet.
+        None => break None,
+        // the text might continue in the next chunk.
+        Some(XmlElement::Text(_)) if iter.is_done() && !at_end => break None,
+        Some(XmlElement::Error(e)) => break Some(e),
+        Some(el) => {
+          f(el);
+          done = iter.bytes_consumed();
+        }
+      }
+    };
+    let result = match failure {
+      Some(e) => Err(self.error(e.offset, e.kind)),
+      None => Ok(()),
+    };
+    self.buffer.copy_within(done..self.len, 0);
+    self.len -= done;
+    self.consumed += done;
+    result
+  }
+
+  /// An error at `offset` bytes into the buffer.
+  fn error(&self, offset: usize, kind: XmlErrorKind) -> XmlError {
+    XmlError { kind, offset: self.consumed + offset }
+  }
+}
@@ -0,0 +1,120 @@
+use super::*;
+
+/// A cursor over the elements of some XML data.
+///
+/// This gives the same elements as an [`ElementIterator`], but with methods
+/// that suit a recursive descent parser better than `Iterator` does: you can
+/// [`peek`](Self::peek) at the next element to decide what to do, and only
+/// [`bump`](Self::bump) past it once you've decided to consume it.
+///
+/// ```rust
+/// # use magnesium::*;
+/// let mut cursor = XmlCursor::new("<list><item/><item/></list>");
+/// assert!(cursor.bump().unwrap().is_start());
+/// let mut count = 0;
+/// while let Some(XmlElement::EmptyTag { .. }) = cursor.peek() {
+///   cursor.bump();
+///   count += 1;
+/// }
+/// assert_eq!(count, 2);
+/// assert_eq!(cursor.bump(), Some(XmlElement::EndTag { name: "list" }));
+/// assert!(cursor.at_end());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct XmlCursor<'s> {
+  iter: ElementIterator<'s>,
+  /// The element that `bump` gave most recently.
+  current: Option<XmlElement<'s>>,
+}
+impl<'s> XmlCursor<'s> {
+  /// Makes a cursor at the start of the XML data.
+  ///
+  /// The input is handled the same as with [`ElementIterator::new`].
+  #[inline]
+  #[must_use]
+  pub fn new(text: &'s str) -> Self {
+    Self { iter: ElementIterator::new(text), current: None }
+  }
+
+  /// Gets the next element without moving the cursor.
+  #[inline]
+  #[must_use]
+  pub fn peek(&self) -> Option<XmlElement<'s>> {
+    self.iter.clone().next()
+  }
+
+  /// Gets the next element and moves the cursor past it.
+  #[inline]
+  pub fn bump(&mut self) -> Option<XmlElement<'s>> {
+    self.current = self.iter.next();
+    self.current
+  }
+
+  /// Iterates the attributes of the element that [`bump`](Self::bump) gave
+  /// most recently.
+  ///
+  /// If that was a `StartTag` or `EmptyTag` this iterates its attributes,
+  /// otherwise (or if `bump` hasn't given an element yet) this iterates
+  /// nothing.
+  ///
+  /// ```rust
+  /// # use magnesium::*;
+  /// let mut cursor = XmlCursor::new(r#"<enum name="A" value="1"/>"#);
+  /// cursor.bump();
+  /// assert_eq!(cursor.current_attributes().find_by_key("value"), Some("1"));
+  /// ```
+  #[inline]
+  #[must_use]
+  pub fn current_attributes(&self) -> TagAttributeIterator<'s> {
+    match self.current {
+      Some(XmlElement::StartTag { attrs, .. })
+      | Some(XmlElement::EmptyTag { attrs, .. }) => {
+        TagAttributeIterator::new(attrs)
+      }
+      _ => TagAttributeIterator::empty(),
+    }
+  }
+
+  /// Gets the input that the cursor hasn't moved past yet.
+  ///
+  /// ```rust
+  /// # use magnesium::*;
+  /// let mut cursor = XmlCursor::new("<a>text</a>");
+  /// cursor.bump();
+  /// assert_eq!(cursor.remaining(), "text</a>");
+  /// ```
+  #[inline]
+  #[must_use]
+  pub fn remaining(&self) -> &'s str {
+    self.iter.remaining()
+  }
+
+  /// Checks if there are no more elements.
+  ///
+  /// This is the same as `peek().is_none()`, so input that can't be parsed
+  /// counts as the end too.
+  #[inline]
+  #[must_use]
+  pub fn at_end(&self) -> bool {
+    self.peek().is_none()
+  }
+}
+
+/// Makes a cursor that gives the same elements as the iterator.
+///
+/// Use this for a cursor with the options of an [`ElementIteratorBuilder`].
+///
+/// ```rust
+/// # use magnesium::*;
+/// let iter =
+///   ElementIteratorBuilder::new().skip_whitespace(true).build("<a> </a>");
+/// let mut cursor = XmlCursor::from(iter);
+/// assert!(cursor.bump().unwrap().is_start());
+/// assert!(cursor.bump().unwrap().is_end());
+/// ```
+impl<'s> From<ElementIterator<'s>> for XmlCursor<'s> {
+  #[inline]
+  fn from(iter: ElementIterator<'s>) -> Self {
+    Self { iter, current: None }
+  }
+}
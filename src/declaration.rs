@@ -0,0 +1,123 @@
+use super::*;
+
+/// The contents of an XML declaration.
+///
+/// Eg: `<?xml version="1.0" encoding="UTF-8" standalone="yes"?>`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct XmlDeclaration<'s> {
+  /// The XML version, which is always given.
+  pub version: &'s str,
+  /// The encoding, if given.
+  pub encoding: Option<&'s str>,
+  /// The standalone status, if given (`yes` is `true`, `no` is `false`).
+  pub standalone: Option<bool>,
+}
+
+/// Parses the XML declaration at the start of some XML data.
+///
+/// Leading whitespace is skipped. The declaration's attributes are parsed with
+/// a [`TagAttributeIterator`].
+///
+/// ```rust
+/// # use magnesium::*;
+/// let xml = r#"<?xml version="1.0" encoding="UTF-8"?><registry/>"#;
+/// let decl = parse_declaration(xml).unwrap();
+/// assert_eq!(decl.version, "1.0");
+/// assert_eq!(decl.encoding, Some("UTF-8"));
+/// assert_eq!(decl.standalone, None);
+/// ```
+///
+/// ## Failure
+/// * If the data doesn't start with a declaration, or it doesn't close.
+/// * If there's no `version`.
+/// * If `standalone` is something other than `yes` or `no`.
+#[must_use]
+pub fn parse_declaration(xml: &str) -> Option<XmlDeclaration<'_>> {
+  let rest = xml.trim_start().strip_prefix("<?xml")?;
+  if !rest.starts_with(char::is_whitespace) {
+    return None;
+  }
+  let (attrs, _rest) = break_on_first_str(rest, "?>")?;
+  let mut decl = XmlDeclaration::default();
+  let mut has_version = false;
+  for TagAttribute { key, value } in TagAttributeIterator::new(attrs) {
+    match key {
+      "version" => {
+        decl.version = value;
+        has_version = true;
+      }
+      "encoding" => decl.encoding = Some(value),
+      "standalone" => {
+        decl.standalone = Some(match value {
+          "yes" => true,
+          "no" => false,
+          _ => return None,
+        })
+      }
+      _ => (),
+    }
+  }
+  if has_version {
+    Some(decl)
+  } else {
+    None
+  }
+}
+
+/// Finds the general entities declared in a DOCTYPE's internal subset.
+///
+/// Each `<!ENTITY name "value">` (or with `'` quotes) gives a `(name, value)`
+/// pair, in the order they appear. The value is exactly as written, so any
+/// character references within it (such as `&#169;`) still need decoding, eg
+/// with [`write_decoded`].
+///
+/// Parameter entities (`<!ENTITY % name ...>`), external entities (declared
+/// with `SYSTEM` or `PUBLIC`), and declarations that don't parse are all
+/// skipped.
+///
+/// ```rust
+/// # use magnesium::*;
+/// let doctype = r#"<!DOCTYPE doc [
+///   <!ENTITY copy "&#169;">
+///   <!ENTITY team 'the team'>
+/// ]>"#;
+/// assert_eq!(
+///   parse_internal_entities(doctype),
+///   [("copy", "&#169;"), ("team", "the team")]
+/// );
+/// ```
+#[cfg(feature = "alloc")]
+#[must_use]
+pub fn parse_internal_entities(doctype: &str) -> Vec<(&str, &str)> {
+  let mut out = Vec::new();
+  let mut rest = doctype;
+  while let Some((_, after)) = break_on_first_str(rest, "<!ENTITY") {
+    rest = after;
+    if let Some((entity, after)) = parse_entity_decl(after) {
+      out.push(entity);
+      rest = after;
+    }
+  }
+  out
+}
+
+/// Parses an entity declaration's body, after the `<!ENTITY`.
+///
+/// Gives the `(name, value)` and the text after the closing `>`.
+#[cfg(feature = "alloc")]
+fn parse_entity_decl(decl: &str) -> Option<((&str, &str), &str)> {
+  if !decl.starts_with(char::is_whitespace) {
+    return None;
+  }
+  let decl = decl.trim_start();
+  let name_end = decl.find(|c: char| c.is_whitespace())?;
+  let (name, decl) = decl.split_at(name_end);
+  if !is_valid_xml_name(name) {
+    return None;
+  }
+  let decl = decl.trim_start();
+  let quote = decl.chars().next().filter(|&c| c == '"' || c == '\'')?;
+  let (value, decl) = break_on_first_char(&decl[1..], quote)?;
+  let rest = decl.trim_start().strip_prefix('>')?;
+  Some(((name, value), rest))
+}
@@ -0,0 +1,261 @@
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+use core::fmt;
+
+/// The five entities that XML predefines, as `(name, text)` pairs.
+///
+/// The name is what goes between the `&` and `;`, and the text is what the
+/// entity stands for. All of the decoding functions in this crate use this
+/// table, so it's the complete list of named entities that they understand.
+///
+/// ```rust
+/// # use magnesium::*;
+/// let (name, text) = XML_PREDEFINED_ENTITIES[0];
+/// assert_eq!((name, text), ("lt", "<"));
+/// ```
+pub const XML_PREDEFINED_ENTITIES: [(&str, &str); 5] =
+  [("lt", "<"), ("gt", ">"), ("amp", "&"), ("quot", "\""), ("apos", "'")];
+
+/// A problem decoding the entities within some XML text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DecodeError {
+  /// What the problem was.
+  pub kind: DecodeErrorKind,
+  /// The byte offset of the problem within the text being decoded.
+  pub offset: usize,
+}
+impl fmt::Display for DecodeError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{} at byte {}", self.kind, self.offset)
+  }
+}
+#[cfg(feature = "std")]
+impl std::error::Error for DecodeError {}
+
+/// The kinds of [`DecodeError`].
+///
+/// This enum is `#[non_exhaustive]`, so that new kinds of problem can be
+/// added without it being a breaking change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum DecodeErrorKind {
+  /// An `&` without a `;` to end the entity.
+  UnterminatedEntity,
+  /// An entity name that isn't one of the five predefined by XML.
+  UnknownEntity,
+  /// A numeric character reference (`&#...;`) that isn't a valid `char`.
+  InvalidCharRef,
+  /// The output sink gave an error.
+  Write,
+}
+impl fmt::Display for DecodeErrorKind {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(match self {
+      Self::UnterminatedEntity => "unterminated entity",
+      Self::UnknownEntity => "unknown entity",
+      Self::InvalidCharRef => "invalid character reference",
+      Self::Write => "error writing the output",
+    })
+  }
+}
+
+/// Decodes escaped XML text, writing the intended text to `out`.
+///
+/// This expands the five predefined entities (`&lt;`, `&gt;`, `&amp;`,
+/// `&quot;`, and `&apos;`, see [`XML_PREDEFINED_ENTITIES`]) as well as decimal
+/// (`&#65;`) and hex (`&#x41;`) character references. Everything else is
+/// written as-is. Because it only needs [`fmt::Write`], this works without
+/// `alloc`.
+///
+/// ```rust
+/// # use magnesium::*;
+/// let mut out = String::new();
+/// write_decoded(&mut out, "1 &lt; 2 &amp;&#x26; &quot;ok&quot;").unwrap();
+/// assert_eq!(out, r#"1 < 2 && "ok""#);
+/// ```
+///
+/// ## Failure
+/// * If an entity is unterminated, unknown, or an invalid character reference.
+///   The error `offset` is the position of that entity's `&`.
+/// * If `out` gives an error.
+///
+/// Any text before the problem will already have been written to `out`.
+pub fn write_decoded<W: fmt::Write>(
+  out: &mut W, text: &str,
+) -> Result<(), DecodeError> {
+  let mut rest = text;
+  loop {
+    let offset = text.len() - rest.len();
+    let write_err = |_| DecodeError { kind: DecodeErrorKind::Write, offset };
+    let amp = match rest.bytes().position(|b| b == b'&') {
+      Some(amp) => amp,
+      None => return out.write_str(rest).map_err(write_err),
+    };
+    out.write_str(&rest[..amp]).map_err(write_err)?;
+    let offset = offset + amp;
+    let fail = |kind| DecodeError { kind, offset };
+    let entity = &rest[amp + 1..];
+    let semi = entity
+      .bytes()
+      .position(|b| b == b';')
+      .ok_or_else(|| fail(DecodeErrorKind::UnterminatedEntity))?;
+    let c = decode_entity(&entity[..semi]).map_err(fail)?;
+    out.write_char(c).map_err(|_| fail(DecodeErrorKind::Write))?;
+    rest = &entity[semi + 1..];
+  }
+}
+
+/// Decodes escaped XML text, keeping any entity it can't decode as is.
+///
+/// The five predefined entities and numeric character references are decoded
+/// the same as with [`write_decoded`]. Anything else starting with `&` (an
+/// unknown entity, a bad character reference, or an `&` that's never closed
+/// with `;`) is copied to the output unchanged instead of being an error.
+///
+/// ```rust
+/// # use magnesium::*;
+/// assert_eq!(decode_xml_lossy("&copy; 1 &lt; 2 &amp; &"), "&copy; 1 < 2 & &");
+/// ```
+#[cfg(feature = "alloc")]
+#[must_use]
+pub fn decode_xml_lossy(text: &str) -> String {
+  let mut out = String::with_capacity(text.len());
+  push_decoded(&mut out, text, None);
+  out
+}
+
+/// Pushes the decoded text onto `out`.
+///
+/// An entity that can't be decoded is kept as is when `unknown` is `None`, or
+/// else replaced with the `unknown` character. If there's no `;` to end the
+/// entity, or something that can't be in an entity name comes before the `;`
+/// (such as the space in `R&D and more;`), then the `&` is a bare `&` and only
+/// the `&` itself is kept or replaced.
+#[cfg(feature = "alloc")]
+pub(crate) fn push_decoded(
+  out: &mut String, text: &str, unknown: Option<char>,
+) {
+  let mut rest = text;
+  while let Some(amp) = rest.bytes().position(|b| b == b'&') {
+    out.push_str(&rest[..amp]);
+    let entity = &rest[amp + 1..];
+    let semi = entity
+      .bytes()
+      .position(|b| !is_entity_name_byte(b))
+      .filter(|&end| entity.as_bytes()[end] == b';');
+    let decoded = semi.and_then(|semi| decode_entity(&entity[..semi]).ok());
+    rest = match (decoded, semi, unknown) {
+      (Some(c), Some(semi), _) => {
+        out.push(c);
+        &entity[semi + 1..]
+      }
+      (None, Some(semi), Some(u)) => {
+        out.push(u);
+        &entity[semi + 1..]
+      }
+      (_, _, u) => {
+        out.push(u.unwrap_or('&'));
+        entity
+      }
+    };
+  }
+  out.push_str(rest);
+}
+
+/// How to handle entities that can't be decoded.
+///
+/// Used with `decode_xml_with_policy` (requires `alloc`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DecodePolicy {
+  /// Panic, like `revert_xml_encoding`.
+  Strict,
+  /// Keep the entity as is, like `decode_xml_lossy`.
+  PassthroughUnknown,
+  /// Replace the entity with the `char` given, such as
+  /// [`REPLACEMENT_CHARACTER`](core::char::REPLACEMENT_CHARACTER).
+  ReplaceUnknownWith(char),
+}
+
+/// Decodes escaped XML text, handling bad entities according to the `policy`.
+///
+/// The five predefined entities and numeric character references are decoded
+/// the same as with [`write_decoded`]. An entity that's unknown, an invalid
+/// character reference, or never closed with `;` is handled by the policy. If
+/// there's no `;` to close the entity, only the `&` itself is replaced.
+///
+/// ```rust
+/// # use magnesium::*;
+/// let text = "&lt;&nbsp;&gt;";
+/// assert_eq!(
+///   decode_xml_with_policy(text, DecodePolicy::PassthroughUnknown),
+///   "<&nbsp;>"
+/// );
+/// assert_eq!(
+///   decode_xml_with_policy(text, DecodePolicy::ReplaceUnknownWith('?')),
+///   "<?>"
+/// );
+/// ```
+///
+/// ## Panics
+/// * With `DecodePolicy::Strict`, if there's an entity that can't be decoded.
+#[cfg(feature = "alloc")]
+#[must_use]
+pub fn decode_xml_with_policy(text: &str, policy: DecodePolicy) -> String {
+  let mut out = String::with_capacity(text.len());
+  match policy {
+    DecodePolicy::Strict => {
+      if let Err(e) = write_decoded(&mut out, text) {
+        panic!("{}", e);
+      }
+    }
+    DecodePolicy::PassthroughUnknown => push_decoded(&mut out, text, None),
+    DecodePolicy::ReplaceUnknownWith(c) => {
+      push_decoded(&mut out, text, Some(c))
+    }
+  }
+  out
+}
+
+/// If a byte can be part of an entity's name (including `#` for numeric
+/// references).
+///
+/// Non-ASCII bytes are allowed, since names can be any Unicode letter.
+#[cfg(feature = "alloc")]
+fn is_entity_name_byte(b: u8) -> bool {
+  b.is_ascii_alphanumeric()
+    || matches!(b, b'#' | b'_' | b'-' | b'.' | b':')
+    || !b.is_ascii()
+}
+
+/// Decodes an entity's name (the part between `&` and `;`) to a `char`.
+fn decode_entity(name: &str) -> Result<char, DecodeErrorKind> {
+  let predefined = XML_PREDEFINED_ENTITIES.iter().find(|(n, _)| *n == name);
+  if let Some((_, text)) = predefined {
+    return text.chars().next().ok_or(DecodeErrorKind::UnknownEntity);
+  }
+  let num = name.strip_prefix('#').ok_or(DecodeErrorKind::UnknownEntity)?;
+  let value = match num.strip_prefix('x') {
+    Some(hex) => u32::from_str_radix(hex, 16),
+    None => num.parse::<u32>(),
+  };
+  value
+    .ok()
+    .filter(|_| !num.contains('+'))
+    .and_then(core::char::from_u32)
+    .ok_or(DecodeErrorKind::InvalidCharRef)
+}
+
+#[test]
+fn test_decode_entity() {
+  assert_eq!(decode_entity("lt"), Ok('<'));
+  assert_eq!(decode_entity("apos"), Ok('\''));
+  assert_eq!(decode_entity("#65"), Ok('A'));
+  assert_eq!(decode_entity("#x41"), Ok('A'));
+  assert_eq!(decode_entity("#x1F600"), Ok('\u{1F600}'));
+  assert_eq!(decode_entity("nbsp"), Err(DecodeErrorKind::UnknownEntity));
+  assert_eq!(decode_entity(""), Err(DecodeErrorKind::UnknownEntity));
+  assert_eq!(decode_entity("#"), Err(DecodeErrorKind::InvalidCharRef));
+  assert_eq!(decode_entity("#+5"), Err(DecodeErrorKind::InvalidCharRef));
+  assert_eq!(decode_entity("#xD800"), Err(DecodeErrorKind::InvalidCharRef));
+  assert_eq!(decode_entity("#x110000"), Err(DecodeErrorKind::InvalidCharRef));
+}
@@ -1,7 +1,14 @@
 use super::*;
 
 /// An element within an XML structure.
+///
+/// This enum is `#[non_exhaustive]`, so that new kinds of element can be
+/// added without it being a breaking change. A `match` on an element outside
+/// of this crate needs a `_` arm. When you only care about one or two kinds of
+/// element, methods such as [`name`](Self::name), [`as_text`](Self::as_text),
+/// and [`is_start`](Self::is_start) avoid the need to match at all.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub enum XmlElement<'s> {
   /// An opening tag with a name and some attributes.
   ///
@@ -14,6 +21,8 @@ pub enum XmlElement<'s> {
     name: &'s str,
     /// Attribute string, parse this with a
     /// [`TagAttributeIterator`](crate::TagAttributeIterator).
+    ///
+    /// Whitespace around the attributes is trimmed off.
     attrs: &'s str,
   },
 
@@ -35,16 +44,48 @@ pub enum XmlElement<'s> {
     ///
     /// Parse this with a
     /// [`TagAttributeIterator`](crate::TagAttributeIterator).
+    ///
+    /// Whitespace around the attributes is trimmed off.
     attrs: &'s str,
   },
 
   /// Text between tags.
-  ///
-  /// If there's a "CDATA" entry it is parsed as a Text element.
   Text(&'s str),
 
+  /// Text between `<![CDATA[` and `]]>`.
+  ///
+  /// Unlike with `Text`, entities in here are *not* escaped, the content is
+  /// exactly what it says.
+  CData(&'s str),
+
   /// Text between `<!--` and `-->`.
+  ///
+  /// `<!---->` is an empty comment. In `<!--->` the `-->` overlaps the
+  /// `<!--`, so it's not a complete comment at all.
   Comment(&'s str),
+
+  /// A processing instruction.
+  ///
+  /// Eg: `<?xml-stylesheet href="style.css"?>`
+  ///
+  /// The XML declaration at the start of a document looks like one of these,
+  /// with a target of `xml`, but it's only given as an element if you ask for
+  /// it with [`ElementIteratorBuilder::keep_declaration`]. A declaration
+  /// that's out of place, such as one after a comment, is skipped the same
+  /// way, or is an error with a strict iterator.
+  ProcessingInstruction {
+    /// The name right after the `<?`.
+    target: &'s str,
+    /// Everything else, with surrounding whitespace trimmed.
+    data: &'s str,
+  },
+
+  /// A problem found by a strict iterator.
+  ///
+  /// Only an iterator made with
+  /// [`ElementIterator::new_strict`](crate::ElementIterator::new_strict) will
+  /// produce this, and it's always the last element of the iteration.
+  Error(XmlError),
 }
 impl<'s> XmlElement<'s> {
   /// Unwraps a `StartTag` variant into the inner `(name, attrs)` pair.
@@ -98,188 +139,2299 @@ impl<'s> XmlElement<'s> {
       _ => panic!("unwrap_comment on non-Comment: {:?}", self),
     }
   }
-}
 
-/// An iterator to walk the elements of some XML data.
-///
-/// This gives you _all_ the elements processed, even a bunch of empty
-/// `XmlElement::Text` elements and `XmlElement::Comment` elements that you
-/// might not care about. You can use
-/// [`filter_map`](core::iter::Iterator::filter_map) to help keep these away
-/// from your main processing code. The crate provides two `filter_map`
-/// compatible functions for you: [`skip_empty_text_elements`] and
-/// [`skip_comments`] filters for you.
-///
-/// The parsing is a little simplistic, and if the iterator gets confused by the
-/// input it will just end the iteration.
-#[derive(Debug, Clone, Default)]
-pub struct ElementIterator<'s> {
-  // Note: this should *initially* be trimmed to the start of the top level XML
-  // tag. From there, any other leading whitespace we see is part of a Text
-  // element.
-  text: &'s str,
-}
-impl<'s> ElementIterator<'s> {
-  /// Makes a new iterator.
+  /// Gets the name of a `StartTag`, `EndTag`, or `EmptyTag`.
   ///
-  /// This works both with and without the initial XML declaration in the
-  /// string. The declaration won't be in the iteration either way.
+  /// ```rust
+  /// # use magnesium::*;
+  /// assert_eq!(XmlElement::EndTag { name: "a" }.name(), Some("a"));
+  /// assert_eq!(XmlElement::Text("a").name(), None);
+  /// ```
   #[inline]
   #[must_use]
-  pub fn new(text: &'s str) -> Self {
-    let text = trim_xml_declaration(text).unwrap_or_default();
-    Self { text }
+  pub fn name(&self) -> Option<&'s str> {
+    match self {
+      Self::StartTag { name, .. }
+      | Self::EndTag { name }
+      | Self::EmptyTag { name, .. } => Some(name),
+      _ => None,
+    }
   }
-}
-impl<'s> Iterator for ElementIterator<'s> {
-  type Item = XmlElement<'s>;
 
+  /// Gets the value of an attribute of a `StartTag` or `EmptyTag`.
+  ///
+  /// This is [`TagAttributeIterator::find_by_key`] on the tag's attributes.
+  /// Other elements don't have attributes, so they always give `None`.
+  ///
+  /// ```rust
+  /// # use magnesium::*;
+  /// let el =
+  ///   ElementIterator::new(r#"<enum name="X" value="1"/>"#).next().unwrap();
+  /// assert_eq!(el.attr("name"), Some("X"));
+  /// assert_eq!(el.attr("alias"), None);
+  /// ```
   #[inline]
   #[must_use]
-  fn next(&mut self) -> Option<Self::Item> {
-    #[allow(clippy::never_loop)]
-    'clear_and_return_none: loop {
-      if self.text.is_empty() {
-        return None;
-      } else if self.text.starts_with("<!CDATA[") {
-        let (cdata, rest) = match break_on_first_str(self.text, "]]>") {
-          Some((cdata, rest)) => (&cdata[8..], rest),
-          None => break 'clear_and_return_none,
-        };
-        self.text = rest;
-        return Some(XmlElement::Text(cdata));
-      } else if self.text.starts_with("<!--") {
-        let (comment, rest) = match break_on_first_str(self.text, "-->") {
-          Some((comment, rest)) => (&comment[4..], rest),
-          None => break 'clear_and_return_none,
-        };
-        self.text = rest;
-        return Some(XmlElement::Comment(comment));
-      } else if self.text.starts_with('<') {
-        let (tag_text, rest) = match break_on_first_char(self.text, '>') {
-          Some((tag_text, rest)) => (&tag_text[1..], rest),
-          None => break 'clear_and_return_none,
-        };
-        self.text = rest;
-        if let Some(stripped) = tag_text.strip_suffix('/') {
-          let (name, attrs) =
-            break_on_first_char(tag_text, ' ').unwrap_or((stripped, "/"));
-          let attrs = &attrs[..attrs.len() - 1];
-          return Some(XmlElement::EmptyTag { name, attrs });
-        } else if let Some(name) = tag_text.strip_prefix('/') {
-          return Some(XmlElement::EndTag { name });
-        } else {
-          let (name, attrs) =
-            break_on_first_char(tag_text, ' ').unwrap_or((tag_text, ""));
-          return Some(XmlElement::StartTag { name, attrs });
-        }
-      } else {
-        let text_end_byte = self.text.find('<').unwrap_or(self.text.len());
-        let (here, rest) = self.text.split_at(text_end_byte);
-        self.text = rest;
-        return Some(XmlElement::Text(here));
+  pub fn attr(&self, key: &str) -> Option<&'s str> {
+    match self {
+      Self::StartTag { attrs, .. } | Self::EmptyTag { attrs, .. } => {
+        TagAttributeIterator::new(attrs).find_by_key(key)
       }
+      _ => None,
+    }
+  }
+
+  /// Gets the text of a `Text` element.
+  ///
+  /// This is the raw text, without any decoding.
+  #[inline]
+  #[must_use]
+  pub fn as_text(&self) -> Option<&'s str> {
+    match self {
+      Self::Text(t) => Some(t),
+      _ => None,
+    }
+  }
+
+  /// Gets the decoded text of a `Text` or `CData` element.
+  ///
+  /// `Text` is decoded with [`decode_xml_lossy`], so an entity that can't be
+  /// decoded is kept as is, and it only allocates when there's an entity. The
+  /// content of `CData` is never decoded, so it's always borrowed.
+  ///
+  /// ```rust
+  /// # use magnesium::*;
+  /// assert_eq!(XmlElement::Text("1 &lt; 2").decoded_text().unwrap(), "1 < 2");
+  /// let cdata = XmlElement::CData("1 &lt; 2");
+  /// assert_eq!(cdata.decoded_text().unwrap(), "1 &lt; 2");
+  /// assert_eq!(XmlElement::Comment("x").decoded_text(), None);
+  /// ```
+  #[cfg(feature = "alloc")]
+  #[must_use]
+  pub fn decoded_text(&self) -> Option<Cow<'s, str>> {
+    match self {
+      Self::Text(t) if t.contains('&') => Some(Cow::Owned(decode_xml_lossy(t))),
+      Self::Text(t) | Self::CData(t) => Some(Cow::Borrowed(t)),
+      _ => None,
+    }
+  }
+
+  /// If the element is an empty (self-closing) tag (`EmptyTag`).
+  #[inline]
+  #[must_use]
+  pub fn is_self_closing(&self) -> bool {
+    matches!(self, Self::EmptyTag { .. })
+  }
+
+  /// If the element is a start tag (`StartTag`).
+  #[inline]
+  #[must_use]
+  pub fn is_start(&self) -> bool {
+    matches!(self, Self::StartTag { .. })
+  }
+
+  /// If the element is an end tag (`EndTag`).
+  #[inline]
+  #[must_use]
+  pub fn is_end(&self) -> bool {
+    matches!(self, Self::EndTag { .. })
+  }
+
+  /// If the element is text (`Text`).
+  #[inline]
+  #[must_use]
+  pub fn is_text(&self) -> bool {
+    matches!(self, Self::Text(_))
+  }
+
+  /// If the element is a comment (`Comment`).
+  #[inline]
+  #[must_use]
+  pub fn is_comment(&self) -> bool {
+    matches!(self, Self::Comment(_))
+  }
+
+  /// Gets the target of a `ProcessingInstruction`.
+  ///
+  /// Use [`is_reserved_pi_target`] to tell the targets reserved by XML (such
+  /// as `xml-stylesheet`) from application specific ones.
+  ///
+  /// ```rust
+  /// # use magnesium::*;
+  /// let el = ElementIterator::new("<?php echo 1; ?><a/>").next().unwrap();
+  /// assert_eq!(el.pi_target(), Some("php"));
+  /// assert_eq!(XmlElement::Text("php").pi_target(), None);
+  /// ```
+  #[inline]
+  #[must_use]
+  pub fn pi_target(&self) -> Option<&'s str> {
+    match self {
+      Self::ProcessingInstruction { target, .. } => Some(target),
+      _ => None,
+    }
+  }
+
+  /// Gives a simple key that identifies this element.
+  ///
+  /// This is `(kind, first, second)`:
+  /// * `kind` is a number for the variant.
+  /// * For tags, `first` is the name and `second` is the attribute string.
+  /// * For a `ProcessingInstruction`, `first` is the target and `second` is the
+  ///   data.
+  /// * For `Text`, `CData`, and `Comment`, `first` is the text.
+  /// * Unused slots are `""`.
+  ///
+  /// | Variant | `kind` |
+  /// |:-|:-|
+  /// | `StartTag` | 0 |
+  /// | `EndTag` | 1 |
+  /// | `EmptyTag` | 2 |
+  /// | `Text` | 3 |
+  /// | `Comment` | 4 |
+  /// | `CData` | 5 |
+  /// | `Error` | 6 |
+  /// | `ProcessingInstruction` | 7 |
+  ///
+  /// The `kind` numbers won't change in future versions, so unlike using the
+  /// derived `Hash` this is a stable way to build your own keys.
+  #[inline]
+  #[must_use]
+  pub fn structural_key(&self) -> (u8, &'s str, &'s str) {
+    match *self {
+      Self::StartTag { name, attrs } => (0, name, attrs),
+      Self::EndTag { name } => (1, name, ""),
+      Self::EmptyTag { name, attrs } => (2, name, attrs),
+      Self::Text(t) => (3, t, ""),
+      Self::Comment(t) => (4, t, ""),
+      Self::CData(t) => (5, t, ""),
+      Self::Error(_) => (6, "", ""),
+      Self::ProcessingInstruction { target, data } => (7, target, data),
     }
-    self.text = "";
-    None
   }
 }
-impl<'s> core::iter::FusedIterator for ElementIterator<'s> {}
 
-/// Filters out `XmlElement::Text(t)` when `t` is only whitespace.
-///
-/// If `t` is more than just whitespace it is unaffected.
+/// Formats the element the way it would appear in XML.
 ///
-/// For use with [`filter_map`](core::iter::Iterator::filter_map) calls on
-/// an [`ElementIterator`].
+/// Text is written as-is, so any entities within it stay encoded, the same as
+/// they were in the input. An `Error` element writes nothing.
 ///
 /// ```rust
 /// # use magnesium::*;
-/// let iter = ElementIterator::new("").filter_map(skip_empty_text_elements);
-/// for element in iter {
-///   println!("{:?}", element);
-/// }
+/// let el = XmlElement::EmptyTag { name: "a", attrs: r#"b="c""# };
+/// assert_eq!(format!("{}", el), r#"<a b="c"/>"#);
+/// assert_eq!(format!("{}", XmlElement::Comment(" x ")), "<!-- x -->");
 /// ```
-///
-/// ## Failure
-/// * If the input is `XmlElement::Text` and the contained text becomes an empty
-///   string after calling [`trim`](str::trim).
-#[inline]
-#[must_use]
-pub fn skip_empty_text_elements(el: XmlElement<'_>) -> Option<XmlElement<'_>> {
-  match el {
-    XmlElement::Text(t) => {
-      if t.trim().is_empty() {
-        None
-      } else {
-        Some(XmlElement::Text(t))
+impl core::fmt::Display for XmlElement<'_> {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    let space = |s: &str| if s.is_empty() { "" } else { " " };
+    match self {
+      Self::StartTag { name, attrs } => {
+        write!(f, "<{}{}{}>", name, space(attrs), attrs)
+      }
+      Self::EndTag { name } => write!(f, "</{}>", name),
+      Self::EmptyTag { name, attrs } => {
+        write!(f, "<{}{}{}/>", name, space(attrs), attrs)
       }
+      Self::Text(t) => f.write_str(t),
+      Self::CData(t) => write!(f, "<![CDATA[{}]]>", t),
+      Self::Comment(t) => write!(f, "<!--{}-->", t),
+      Self::ProcessingInstruction { target, data } => {
+        write!(f, "<?{}{}{}?>", target, space(data), data)
+      }
+      Self::Error(_) => Ok(()),
     }
-    other => Some(other),
   }
 }
 
-/// Filters out `XmlElement::Comment(_)`.
+/// An element equals a string when it's a tag with that name.
 ///
-/// For use with [`filter_map`](core::iter::Iterator::filter_map) calls on
-/// an [`ElementIterator`].
+/// This is true for `StartTag`, `EndTag`, and `EmptyTag` elements with a
+/// matching name. Other elements never equal any string, even if their text
+/// matches.
 ///
 /// ```rust
 /// # use magnesium::*;
-/// let iter = ElementIterator::new("").filter_map(skip_comments);
-/// for element in iter {
-///   println!("{:?}", element);
-/// }
+/// assert!(XmlElement::StartTag { name: "enums", attrs: "" } == "enums");
+/// assert!(XmlElement::EndTag { name: "enums" } == "enums");
+/// assert!(XmlElement::Text("enums") != "enums");
 /// ```
-///
-/// ## Failure
-/// * If the input is `XmlElement::Comment`.
-#[inline]
-#[must_use]
-pub fn skip_comments(el: XmlElement<'_>) -> Option<XmlElement<'_>> {
-  match el {
-    XmlElement::Comment(_) => None,
-    other => Some(other),
+impl<'s, 'a> PartialEq<&'a str> for XmlElement<'s> {
+  #[inline]
+  fn eq(&self, other: &&'a str) -> bool {
+    match self {
+      Self::StartTag { name, .. }
+      | Self::EndTag { name }
+      | Self::EmptyTag { name, .. } => name == other,
+      _ => false,
+    }
   }
 }
 
-/// Applies `str::trim` to a `Text` element. No effect otherwise.
+/// Parses a string that holds exactly one element.
 ///
-/// For use with [`map`](core::iter::Iterator::map) calls on
-/// an [`ElementIterator`].
+/// Whitespace around the element is ignored. Unlike with an
+/// [`ElementIterator`], nothing else is skipped, so an XML declaration is
+/// parsed as a `ProcessingInstruction` element.
 ///
 /// ```rust
 /// # use magnesium::*;
-/// let iter = ElementIterator::new("<a>  z</a>").map(trim_text);
-/// for element in iter {
-///   println!("{:?}", element);
-/// }
+/// use core::convert::TryFrom;
+/// assert_eq!(
+///   XmlElement::try_from(" <a x='1'/> "),
+///   Ok(XmlElement::EmptyTag { name: "a", attrs: "x='1'" })
+/// );
+/// let err = XmlError { kind: XmlErrorKind::TrailingContent, offset: 4 };
+/// assert_eq!(XmlElement::try_from("<a/><b/>"), Err(err));
 /// ```
-#[inline]
-#[must_use]
-pub fn trim_text(el: XmlElement<'_>) -> XmlElement<'_> {
-  match el {
-    XmlElement::Text(t) => XmlElement::Text(t.trim()),
-    other => other,
+///
+/// ## Failure
+/// * `UnexpectedEnd` if there's no complete element.
+/// * `TrailingContent` if there's anything after the first element.
+/// * Any other problem that would end the iteration of an [`ElementIterator`],
+///   such as an `EmptyTagName`.
+impl<'s> core::convert::TryFrom<&'s str> for XmlElement<'s> {
+  type Error = XmlError;
+
+  #[inline]
+  fn try_from(text: &'s str) -> Result<Self, XmlError> {
+    let mut iter = ElementIterator::with_body(text, text.trim());
+    let el = match iter.parse_next() {
+      Some(XmlElement::Error(e)) => return Err(e),
+      Some(el) => el,
+      None => {
+        let offset = text.len() - text.trim_start().len();
+        return Err(XmlError { kind: XmlErrorKind::UnexpectedEnd, offset });
+      }
+    };
+    if iter.is_done() {
+      Ok(el)
+    } else {
+      Err(iter.error_at(iter.remaining(), XmlErrorKind::TrailingContent))
+    }
   }
 }
 
-/// Remove the XML declaration (and leading whitespace), if any.
-/// ## Failure
-/// * If the declaration opens but _doesn't_ close, this fails.
-fn trim_xml_declaration(mut text: &str) -> Option<&str> {
-  text = text.trim();
-  if text.starts_with("<?xml") {
-    break_on_first_str(text.trim_start(), "?>")
-      .map(|(_decl, rest)| rest.trim_start())
-  } else {
-    Some(text)
-  }
+/// An iterator to walk the elements of some XML data.
+///
+/// This gives you _all_ the elements processed, even a bunch of empty
+/// `XmlElement::Text` elements and `XmlElement::Comment` elements that you
+/// might not care about. You can use
+/// [`filter_map`](core::iter::Iterator::filter_map) to help keep these away
+/// from your main processing code. The crate provides two `filter_map`
+/// compatible functions for you: [`skip_empty_text_elements`] and
+/// [`skip_comments`] filters for you.
+///
+/// The parsing is a little simplistic, and if the iterator gets confused by the
+/// input it will just end the iteration. This includes when there's a tag with
+/// an empty name, such as `<>` or `</>`.
+///
+/// The iterator never panics, whatever the input `&str` is.
+///
+/// A `Default` iterator has no input, so it gives no elements. See also
+/// [`empty`](Self::empty).
+#[derive(Debug, Clone, Default)]
+pub struct ElementIterator<'s> {
+  // Note: this should *initially* be trimmed to the start of the top level XML
+  // tag. From there, any other leading whitespace we see is part of a Text
+  // element.
+  text: &'s str,
+  /// The full input, for computing byte offsets.
+  source: &'s str,
+  /// The whitespace that was trimmed from before the body.
+  prologue: &'s str,
+  /// Where `text` started, for [`reset`](Self::reset).
+  body: &'s str,
+  strict: bool,
+  skip_whitespace: bool,
+  skip_comments: bool,
+  depth: usize,
+  /// Accepted comment ends, or `None` for just `-->`.
+  comment_terminators: Option<&'s [&'s str]>,
+  /// An extra character (beyond `<`) that text elements are split at.
+  text_boundary: Option<char>,
+  /// Give an XML declaration that's out of place instead of skipping it.
+  keep_declaration: bool,
+  /// The text might only be a prefix of the input, so an open quote at the
+  /// end makes a tag incomplete rather than unbalanced.
+  partial_input: bool,
 }
+impl<'s> ElementIterator<'s> {
+  /// Makes an iterator that gives no elements.
+  ///
+  /// This is the same as `ElementIterator::default()`, and is handy as a
+  /// placeholder.
+  ///
+  /// ```rust
+  /// # use magnesium::*;
+  /// assert_eq!(ElementIterator::empty().next(), None);
+  /// ```
+  #[inline]
+  #[must_use]
+  pub fn empty() -> Self {
+    Self::default()
+  }
 
-#[test]
-fn test_trim_xml_declaration() {
-  assert_eq!(trim_xml_declaration(""), Some(""));
+  /// Makes a new iterator.
+  ///
+  /// This works both with and without the initial XML declaration in the
+  /// string. The declaration won't be in the iteration either way.
+  #[inline]
+  #[must_use]
+  pub fn new(text: &'s str) -> Self {
+    Self::with_body(text, trim_xml_declaration(text).unwrap_or_default())
+  }
+
+  /// Makes an iterator over the `body` part of the full `source` input.
+  pub(crate) fn with_body(source: &'s str, body: &'s str) -> Self {
+    let prologue = match byte_offset_of(body, source) {
+      Some(offset) => {
+        let before = &source[..offset];
+        &before[before.trim_end().len()..]
+      }
+      None => "",
+    };
+    Self { text: body, source, prologue, body, ..Self::default() }
+  }
+
+  /// Marks the text as possibly being only a prefix of the full input.
+  ///
+  /// Used by the incremental parsers, so that the elements they find don't
+  /// depend on how much of the input has arrived.
+  #[inline]
+  #[must_use]
+  pub(crate) fn partial_input(self) -> Self {
+    Self { partial_input: true, ..self }
+  }
+
+  /// Goes back to the first element, so the input can be read again.
+  ///
+  /// The iterator's settings are kept, and the XML declaration doesn't need to
+  /// be found again.
+  ///
+  /// ```rust
+  /// # use magnesium::*;
+  /// let mut iter = ElementIterator::new("<a/><b/>");
+  /// assert_eq!(iter.by_ref().count(), 2);
+  /// iter.reset();
+  /// assert_eq!(iter.next().and_then(|el| el.name()), Some("a"));
+  /// ```
+  #[inline]
+  pub fn reset(&mut self) {
+    self.text = self.body;
+    self.depth = 0;
+  }
+
+  /// Gets the whitespace that was skipped over before the first element.
+  ///
+  /// When the iterator is made, the XML declaration and any whitespace around
+  /// it is skipped. This gives back the whitespace that came after the
+  /// declaration (or from the start of the input, if there's no declaration),
+  /// so that the input can be reconstructed exactly.
+  ///
+  /// ```rust
+  /// # use magnesium::*;
+  /// let xml = "<?xml version=\"1.0\"?>\n\n<root/>";
+  /// assert_eq!(ElementIterator::new(xml).prologue_whitespace(), "\n\n");
+  /// ```
+  #[inline]
+  #[must_use]
+  pub fn prologue_whitespace(&self) -> &'s str {
+    self.prologue
+  }
+
+  /// Makes a new iterator that checks the input more carefully.
+  ///
+  /// A strict iterator gives the same elements as a normal iterator, except
+  /// that when it finds a problem that a normal iterator would let slide it
+  /// produces an [`XmlElement::Error`] and then ends the iteration.
+  ///
+  /// Problems currently detected:
+  /// * Non-whitespace text outside of the root element.
+  /// * A tag with an empty name, such as `<>`, `</>`, or `< />`. A normal
+  ///   iterator just ends the iteration when it sees one of these.
+  /// * An end tag with something after the name, such as `</a b="1">`. A normal
+  ///   iterator gives the `EndTag` with just the name.
+  /// * Whitespace before a tag's name, such as `< a>`. A normal iterator
+  ///   ignores the whitespace.
+  /// * The sequence `]]>` within text, outside of a CDATA section. A normal
+  ///   iterator gives it as part of the `Text`.
+  /// * A comment that's never closed, such as `<!--->` (where the `-->`
+  ///   overlaps the `<!--`), or that has `--` within it or ends with `-`. A
+  ///   normal iterator ends the iteration at an unclosed comment, and gives the
+  ///   others as a `Comment`.
+  /// * A `/` right after a tag's name that doesn't end the tag, such as
+  ///   `<a/b>`. A normal iterator ends the name at the `/`, giving a `StartTag`
+  ///   named `a` with `/b` as the `attrs`.
+  /// * An XML declaration that isn't at the very start of the input, such as
+  ///   one after a comment. A normal iterator skips it.
+  ///
+  /// ```rust
+  /// # use magnesium::*;
+  /// let mut iter = ElementIterator::new_strict("oops<root/>");
+  /// let err = XmlError { kind: XmlErrorKind::TextOutsideRoot, offset: 0 };
+  /// assert_eq!(iter.next(), Some(XmlElement::Error(err)));
+  /// assert_eq!(iter.next(), None);
+  /// ```
+  #[inline]
+  #[must_use]
+  pub fn new_strict(text: &'s str) -> Self {
+    Self { strict: true, ..Self::new(text) }
+  }
+
+  /// Makes a new iterator that accepts other ways to end a comment.
+  ///
+  /// Normally only `-->` ends a comment. With this constructor a comment ends
+  /// at the earliest of any of the `terminators` given instead, which can help
+  /// with (incorrect) input that ends comments with something like `--!>`.
+  /// The terminator is not part of the `Comment` element's text.
+  ///
+  /// If `terminators` is empty then no comment can ever end, and the
+  /// iteration will stop at the first comment.
+  ///
+  /// ```rust
+  /// # use magnesium::*;
+  /// let xml = "<!-- note --!><root/>";
+  /// let mut iter =
+  ///   ElementIterator::new_with_comment_terminators(xml, &["-->", "--!>"]);
+  /// assert_eq!(iter.next(), Some(XmlElement::Comment(" note ")));
+  /// assert_eq!(
+  ///   iter.next(),
+  ///   Some(XmlElement::EmptyTag { name: "root", attrs: "" })
+  /// );
+  /// ```
+  #[inline]
+  #[must_use]
+  pub fn new_with_comment_terminators(
+    text: &'s str, terminators: &'s [&'s str],
+  ) -> Self {
+    Self { comment_terminators: Some(terminators), ..Self::new(text) }
+  }
+
+  /// Makes a new iterator that also splits text at an `extra` character.
+  ///
+  /// Normally a `Text` element goes until the next `<`. With this constructor
+  /// text also ends just before any `extra` character, which then begins the
+  /// next `Text` element. This can help when preprocessing something like
+  /// templated XML. Only text is affected, not tags, comments, or other
+  /// elements.
+  ///
+  /// ```rust
+  /// # use magnesium::*;
+  /// let xml = "<a>x{y}</a>";
+  /// let mut iter = ElementIterator::new_with_text_boundary(xml, '{');
+  /// let a = XmlElement::StartTag { name: "a", attrs: "" };
+  /// assert_eq!(iter.next(), Some(a));
+  /// assert_eq!(iter.next(), Some(XmlElement::Text("x")));
+  /// assert_eq!(iter.next(), Some(XmlElement::Text("{y}")));
+  /// assert_eq!(iter.next(), Some(XmlElement::EndTag { name: "a" }));
+  /// ```
+  #[inline]
+  #[must_use]
+  pub fn new_with_text_boundary(text: &'s str, extra: char) -> Self {
+    Self { text_boundary: Some(extra), ..Self::new(text) }
+  }
+
+  /// Iterates the direct children of the tag that was just opened.
+  ///
+  /// Call this right after the iterator gives a `StartTag` named `name`. The
+  /// new iterator gives each child `StartTag` and `EmptyTag`, skipping over
+  /// everything within the child tags, and it ends after the `EndTag` that
+  /// closes `name`. Text, comments, and stray end tags directly within the
+  /// parent are skipped.
+  ///
+  /// ```rust
+  /// # use magnesium::*;
+  /// let xml = r#"<enums><enum name="A"/><g><enum name="B"/></g></enums>"#;
+  /// let mut iter = ElementIterator::new(xml);
+  /// let _enums = iter.next();
+  /// let mut children = iter.children_of("enums");
+  /// assert_eq!(
+  ///   children.next(),
+  ///   Some(XmlElement::EmptyTag { name: "enum", attrs: r#"name="A""# })
+  /// );
+  /// assert_eq!(
+  ///   children.next(),
+  ///   Some(XmlElement::StartTag { name: "g", attrs: "" })
+  /// );
+  /// assert_eq!(children.next(), None);
+  /// ```
+  #[inline]
+  #[must_use]
+  pub fn children_of<'i>(&'i mut self, name: &'i str) -> ChildElements<'i, 's> {
+    ChildElements { iter: self, name, depth: 0, done: false }
+  }
+
+  /// Checks if there's no more input for the iterator to process.
+  ///
+  /// Once this is `true`, `next` will always give `None`. If the iterator is
+  /// skipping some elements (see [`ElementIteratorBuilder`]) then this can
+  /// still be `false` when there's only skippable input left.
+  ///
+  /// ```rust
+  /// # use magnesium::*;
+  /// let mut iter = ElementIterator::new("<a/>");
+  /// assert!(!iter.is_done());
+  /// let _a = iter.next();
+  /// assert!(iter.is_done());
+  /// ```
+  #[inline]
+  #[must_use]
+  pub fn is_done(&self) -> bool {
+    self.text.is_empty()
+  }
+
+  /// Gets the name of the next element, if it's a tag, without advancing.
+  ///
+  /// This works for `StartTag`, `EndTag`, and `EmptyTag` elements. If the next
+  /// element is anything else (or there isn't one) this gives `None`.
+  ///
+  /// ```rust
+  /// # use magnesium::*;
+  /// let mut iter = ElementIterator::new("<a>text</a>");
+  /// assert_eq!(iter.peek_name(), Some("a"));
+  /// let _a = iter.next();
+  /// assert_eq!(iter.peek_name(), None);
+  /// ```
+  #[must_use]
+  pub fn peek_name(&self) -> Option<&'s str> {
+    self.clone().next()?.name()
+  }
+
+  /// Gets how many bytes of the input have been processed so far.
+  ///
+  /// This counts from the start of the full input given when the iterator was
+  /// made, so it can be compared against the input's length to report
+  /// progress. Once the iterator is done this is the full input length,
+  /// including any trailing whitespace.
+  ///
+  /// ```rust
+  /// # use magnesium::*;
+  /// let xml = "<a>text</a>";
+  /// let mut iter = ElementIterator::new(xml);
+  /// let _a = iter.next();
+  /// assert_eq!(iter.bytes_consumed(), 3);
+  /// iter.by_ref().for_each(drop);
+  /// assert_eq!(iter.bytes_consumed(), xml.len());
+  /// ```
+  #[inline]
+  #[must_use]
+  pub fn bytes_consumed(&self) -> usize {
+    if self.text.is_empty() {
+      self.source.len()
+    } else {
+      byte_offset_of(self.text, self.source).unwrap_or(0)
+    }
+  }
+
+  /// Gives each element along with the byte offset where it starts.
+  ///
+  /// The offset is within the full input given when the iterator was made.
+  /// Since the text of a `Text` element is exactly as it appears in the input,
+  /// the text ends at the offset plus the length of the text, which is where
+  /// the following `<` is (or the end of the input).
+  ///
+  /// Note that whitespace at the very end of the input is trimmed when the
+  /// iterator is made, so a final `Text` element never includes it.
+  ///
+  /// ```rust
+  /// # use magnesium::*;
+  /// let xml = "<a>text</a>";
+  /// let mut iter = ElementIterator::new(xml).indexed();
+  /// assert_eq!(
+  ///   iter.next(),
+  ///   Some((0, XmlElement::StartTag { name: "a", attrs: "" }))
+  /// );
+  /// let (start, text) = iter.next().unwrap();
+  /// let end = start + text.as_text().unwrap().len();
+  /// assert_eq!(&xml[end..], "</a>");
+  /// ```
+  #[inline]
+  #[must_use]
+  pub fn indexed(self) -> Indexed<'s> {
+    Indexed { iter: self }
+  }
+
+  /// Gives each element along with the name of the tag that it's within.
+  ///
+  /// The name is that of the nearest `StartTag` that's still open, or `None`
+  /// for an element at the top level (outside of the root). An `EndTag` is
+  /// given with the parent of the tag that it closes, the same as its
+  /// `StartTag` was.
+  ///
+  /// The open tags are kept on a stack. An `EndTag` closes the nearest open
+  /// tag with the same name, along with any unclosed tags within that one. An
+  /// `EndTag` that doesn't match any open tag doesn't change the stack.
+  ///
+  /// ```rust
+  /// # use magnesium::*;
+  /// let xml = "<enums><enum/><enum/></enums>";
+  /// let mut iter = ElementIterator::new(xml).with_parent();
+  /// assert_eq!(iter.next().unwrap().0, None);
+  /// assert_eq!(iter.next().unwrap().0, Some("enums"));
+  /// assert_eq!(iter.next().unwrap().0, Some("enums"));
+  /// assert_eq!(iter.next().unwrap().0, None);
+  /// ```
+  #[cfg(feature = "alloc")]
+  #[inline]
+  #[must_use]
+  pub fn with_parent(self) -> WithParent<'s> {
+    WithParent { iter: self, stack: Vec::new() }
+  }
+
+  /// Gives each element along with its path from the outermost tag.
+  ///
+  /// For a `StartTag`, `EmptyTag`, or `EndTag` the path ends with that tag's
+  /// own name. For anything else, the path is the tags that it's within. The
+  /// path [displays](core::fmt::Display) as the names joined with `/`, which
+  /// is handy for error messages.
+  ///
+  /// The open tags are tracked the same as with
+  /// [`with_parent`](Self::with_parent). Each path is its own copy of the
+  /// names, so it can be kept after the iterator moves on.
+  ///
+  /// ```rust
+  /// # use magnesium::*;
+  /// let xml = "<registry><types><type>GLenum</type></types></registry>";
+  /// let (path, _) = ElementIterator::new(xml).with_path().nth(3).unwrap();
+  /// assert_eq!(path.to_string(), "registry/types/type");
+  /// assert_eq!(path.names(), ["registry", "types", "type"]);
+  /// ```
+  #[cfg(feature = "alloc")]
+  #[inline]
+  #[must_use]
+  pub fn with_path(self) -> WithPath<'s> {
+    WithPath { iter: self, stack: Vec::new() }
+  }
+
+  /// As `with_parent` (which requires `alloc`), but with a fixed size stack.
+  ///
+  /// This doesn't allocate: the names of the open tags are kept in an array
+  /// of `N` entries.
+  ///
+  /// ## Overflow
+  ///
+  /// The stack saturates. Once more than `N` tags are open, the names of the
+  /// deeper tags aren't recorded, but the depth is still counted. An element
+  /// whose parent wasn't recorded is given with `None` as the parent, and an
+  /// `EndTag` at that depth closes the innermost open tag whatever its name
+  /// is. Once the depth is back to `N` or less, parents are given as normal.
+  ///
+  /// Use [`depth`](WithParentBounded::depth) to tell a top level element apart
+  /// from one that's too deep.
+  ///
+  /// ```rust
+  /// # use magnesium::*;
+  /// let xml = "<a><b><c/></b></a>";
+  /// let parents: Vec<_> = ElementIterator::new(xml)
+  ///   .with_parent_bounded::<1>()
+  ///   .map(|(parent, _)| parent)
+  ///   .collect();
+  /// assert_eq!(parents, [None, Some("a"), None, Some("a"), None]);
+  /// ```
+  #[inline]
+  #[must_use]
+  pub fn with_parent_bounded<const N: usize>(self) -> WithParentBounded<'s, N> {
+    WithParentBounded { iter: self, stack: [""; N], depth: 0 }
+  }
+
+  /// Gives each element along with the byte range of its source text.
+  ///
+  /// The range is within the full input given when the iterator was made, and
+  /// covers all of the element, including the `<` and `>` of a tag, the
+  /// `<!--` and `-->` of a comment, and so on. The ranges are in order and
+  /// non-overlapping, with gaps where elements are skipped. That includes the
+  /// trimmed XML declaration and any whitespace at the start or end of the
+  /// input, a misplaced declaration that's skipped, and anything left out by
+  /// settings such as
+  /// [`skip_whitespace`](ElementIteratorBuilder::skip_whitespace).
+  ///
+  /// ```rust
+  /// # use magnesium::*;
+  /// let xml = "<a>text<!-- c --></a>";
+  /// let ranges: Vec<_> =
+  ///   ElementIterator::new(xml).with_byte_ranges().map(|(r, _)| r).collect();
+  /// assert_eq!(ranges, [0..3, 3..7, 7..17, 17..21]);
+  /// assert_eq!(&xml[ranges[2].clone()], "<!-- c -->");
+  /// ```
+  #[inline]
+  #[must_use]
+  pub fn with_byte_ranges(self) -> WithByteRanges<'s> {
+    WithByteRanges { iter: self }
+  }
+
+  /// Renames tags as they're iterated.
+  ///
+  /// The name of each `StartTag`, `EndTag`, and `EmptyTag` is passed to `f`,
+  /// and the tag is given with whatever name `f` returns. Other elements are
+  /// unaffected.
+  ///
+  /// ```rust
+  /// # use magnesium::*;
+  /// let mut iter = ElementIterator::new("<old/>").map_tags(|name| {
+  ///   if name == "old" {
+  ///     "new"
+  ///   } else {
+  ///     name
+  ///   }
+  /// });
+  /// assert_eq!(
+  ///   iter.next(),
+  ///   Some(XmlElement::EmptyTag { name: "new", attrs: "" })
+  /// );
+  /// ```
+  #[inline]
+  #[must_use]
+  pub fn map_tags<F>(self, f: F) -> MapTags<'s, F>
+  where
+    F: FnMut(&'s str) -> &'s str,
+  {
+    MapTags { iter: self, f }
+  }
+
+  /// Checks each element with `f`, giving `Err` for any that fail.
+  ///
+  /// Each element is passed to `f`. If `f` gives `Ok` then the element is
+  /// given as `Ok`, otherwise the error from `f` is given and the iteration
+  /// ends. This makes it easy to stop at the first invalid element with `?`.
+  ///
+  /// ```rust
+  /// # use magnesium::*;
+  /// fn check(xml: &str) -> Result<usize, &'static str> {
+  ///   let mut count = 0;
+  ///   let iter = ElementIterator::new(xml).validated(|el| match el {
+  ///     XmlElement::EmptyTag { name: "br", attrs } if !attrs.is_empty() => {
+  ///       Err("br with attributes")
+  ///     }
+  ///     _ => Ok(()),
+  ///   });
+  ///   for el in iter {
+  ///     let _el = el?;
+  ///     count += 1;
+  ///   }
+  ///   Ok(count)
+  /// }
+  /// assert_eq!(check("<p>a<br/>b</p>"), Ok(5));
+  /// assert_eq!(check("<p>a<br x='1'/>b</p>"), Err("br with attributes"));
+  /// ```
+  #[inline]
+  #[must_use]
+  pub fn validated<F, E>(self, f: F) -> Validated<'s, F>
+  where
+    F: FnMut(&XmlElement<'s>) -> Result<(), E>,
+  {
+    Validated { iter: self, f }
+  }
+
+  /// Advances to the first element that `pred` accepts, and gives it.
+  ///
+  /// This is like [`find`](Iterator::find), but the predicate takes the
+  /// element by reference, which reads better with `matches!` and with
+  /// methods such as [`XmlElement::is_self_closing`].
+  ///
+  /// ```rust
+  /// # use magnesium::*;
+  /// let mut iter = ElementIterator::new("<a><b/><c/></a>");
+  /// let b = iter.find_element(XmlElement::is_self_closing);
+  /// assert_eq!(b, Some(XmlElement::EmptyTag { name: "b", attrs: "" }));
+  /// let c = XmlElement::EmptyTag { name: "c", attrs: "" };
+  /// assert_eq!(iter.next(), Some(c));
+  /// ```
+  ///
+  /// ## Failure
+  /// * If no element matches before the input runs out.
+  #[inline]
+  pub fn find_element<F>(&mut self, pred: F) -> Option<XmlElement<'s>>
+  where
+    F: Fn(&XmlElement<'s>) -> bool,
+  {
+    self.find(|el| pred(el))
+  }
+
+  /// Iterates only the `StartTag` and `EmptyTag` elements with a given name.
+  ///
+  /// Tags are matched anywhere in the document, at any depth. All other
+  /// elements are skipped.
+  ///
+  /// ```rust
+  /// # use magnesium::*;
+  /// let xml = "<a><b x='1'/><c><b>2</b></c></a>";
+  /// let mut iter = ElementIterator::new(xml).named("b");
+  /// assert_eq!(
+  ///   iter.next(),
+  ///   Some(XmlElement::EmptyTag { name: "b", attrs: "x='1'" })
+  /// );
+  /// let b = XmlElement::StartTag { name: "b", attrs: "" };
+  /// assert_eq!(iter.next(), Some(b));
+  /// assert_eq!(iter.next(), None);
+  /// ```
+  #[inline]
+  #[must_use]
+  pub fn named(self, name: &'s str) -> Named<'s> {
+    Named { iter: self, name }
+  }
+
+  /// Gets the raw XML within the tag that was just opened.
+  ///
+  /// Call this right after the iterator gives a `StartTag` named `name`. This
+  /// advances the iterator past the matching `EndTag` (balancing any nested
+  /// tags along the way) and gives back everything in between the two tags,
+  /// exactly as it appears in the input.
+  ///
+  /// ```rust
+  /// # use magnesium::*;
+  /// let mut iter = ElementIterator::new("<wrap><a/><b>text</b></wrap><c/>");
+  /// let _wrap = iter.next();
+  /// assert_eq!(iter.inner_xml("wrap"), Some("<a/><b>text</b>"));
+  /// let c = XmlElement::EmptyTag { name: "c", attrs: "" };
+  /// assert_eq!(iter.next(), Some(c));
+  /// ```
+  ///
+  /// ## Failure
+  /// * If the input ends (or can't be parsed) before the matching `EndTag`.
+  #[must_use]
+  pub fn inner_xml(&mut self, name: &str) -> Option<&'s str> {
+    let body = self.text;
+    let mut depth = 0_usize;
+    loop {
+      let before = self.text;
+      match self.parse_next() {
+        Some(XmlElement::StartTag { .. }) => depth += 1,
+        Some(XmlElement::EndTag { name: end_name }) if depth == 0 => {
+          // a stray close that isn't for this tag is just skipped.
+          if end_name == name {
+            self.depth = self.depth.saturating_sub(1);
+            return Some(&body[..body.len() - before.len()]);
+          }
+        }
+        Some(XmlElement::EndTag { .. }) => depth -= 1,
+        Some(XmlElement::Error(_)) | None => {
+          self.text = "";
+          return None;
+        }
+        Some(_) => (),
+      }
+    }
+  }
+
+  /// Skips over the rest of the tag that was just opened.
+  ///
+  /// Call this right after the iterator gives a `StartTag` named `name`. This
+  /// advances the iterator past the matching `EndTag` (balancing any nested
+  /// tags along the way), so that the next element is whatever follows the
+  /// skipped tag. This is the same as [`inner_xml`](Self::inner_xml), but for
+  /// when you don't want the content.
+  ///
+  /// Gives `true` if the matching `EndTag` was found, or `false` if the input
+  /// ended (or couldn't be parsed) first.
+  ///
+  /// ```rust
+  /// # use magnesium::*;
+  /// let mut iter = ElementIterator::new("<r><ext><a/></ext><b/></r>");
+  /// let _r = iter.next();
+  /// let _ext = iter.next();
+  /// assert!(iter.skip_element("ext"));
+  /// let b = XmlElement::EmptyTag { name: "b", attrs: "" };
+  /// assert_eq!(iter.next(), Some(b));
+  /// ```
+  #[inline]
+  pub fn skip_element(&mut self, name: &str) -> bool {
+    self.inner_xml(name).is_some()
+  }
+
+  /// Gets the raw text within the tag that was just opened, without decoding.
+  ///
+  /// Call this right after the iterator gives a `StartTag` named `name`. Like
+  /// with [`inner_xml`](Self::inner_xml), this advances the iterator past the
+  /// matching `EndTag`. The text is borrowed straight from the input, entities
+  /// and all, so this only works when the content is one contiguous run of
+  /// text. If there's a child tag, comment, CDATA section, or anything else
+  /// within the tag then this gives `None` (the iterator is still advanced).
+  /// For that case, use `inner_xml` and then something like
+  /// `decode_element_text` (requires `alloc`) to get all the text.
+  ///
+  /// ```rust
+  /// # use magnesium::*;
+  /// let xml = "<r><a>1 &lt; 2</a><b>x<i/>y</b></r>";
+  /// let mut iter = ElementIterator::new(xml);
+  /// let _r = iter.next();
+  /// let _a = iter.next();
+  /// assert_eq!(iter.raw_inner_text("a"), Some("1 &lt; 2"));
+  /// let _b = iter.next();
+  /// assert_eq!(iter.raw_inner_text("b"), None);
+  /// assert_eq!(iter.next(), Some(XmlElement::EndTag { name: "r" }));
+  /// ```
+  ///
+  /// ## Failure
+  /// * If the content isn't only text.
+  /// * Same as with [`inner_xml`](Self::inner_xml).
+  #[must_use]
+  pub fn raw_inner_text(&mut self, name: &str) -> Option<&'s str> {
+    self.inner_xml(name).filter(|inner| !inner.contains('<'))
+  }
+
+  /// Gets the raw XML within the tag that was just opened, trimmed of the
+  /// whitespace around it.
+  ///
+  /// This is [`inner_xml`](Self::inner_xml) with [`trim`](str::trim) applied,
+  /// which is handy when the content is indented on its own lines.
+  ///
+  /// ```rust
+  /// # use magnesium::*;
+  /// let mut iter = ElementIterator::new("<wrap>\n  <a/>\n</wrap>");
+  /// let _wrap = iter.next();
+  /// assert_eq!(iter.inner_xml_trimmed("wrap"), Some("<a/>"));
+  /// ```
+  ///
+  /// ## Failure
+  /// * Same as with [`inner_xml`](Self::inner_xml).
+  #[must_use]
+  pub fn inner_xml_trimmed(&mut self, name: &str) -> Option<&'s str> {
+    self.inner_xml(name).map(str::trim)
+  }
+
+  /// Pairs each `StartTag` with the byte offset of its matching `EndTag`.
+  ///
+  /// This does one pass over the rest of the input, using a stack to match up
+  /// the tags. Each offset is where the `</` of the `EndTag` begins within the
+  /// full input, so `&xml[start..end]` for a start tag's own offset can later
+  /// slice out its whole subtree without scanning again. The output is in the
+  /// same order as the start tags appear.
+  ///
+  /// A start tag that's never closed is left out of the output, and a stray
+  /// end tag is skipped. An end tag that closes an outer tag also implicitly
+  /// closes (and so drops) any unclosed tags within it. The iteration stops
+  /// at the end of the input or when the input can't be parsed.
+  ///
+  /// ```rust
+  /// # use magnesium::*;
+  /// let xml = "<a><b/><c>x</c></a>";
+  /// let pairs = ElementIterator::new(xml).zip_with_end_tags();
+  /// assert_eq!(
+  ///   pairs,
+  ///   vec![
+  ///     (XmlElement::StartTag { name: "a", attrs: "" }, 15),
+  ///     (XmlElement::StartTag { name: "c", attrs: "" }, 11),
+  ///   ]
+  /// );
+  /// assert_eq!(&xml[15..], "</a>");
+  /// ```
+  #[cfg(feature = "alloc")]
+  #[must_use]
+  pub fn zip_with_end_tags(mut self) -> Vec<(XmlElement<'s>, usize)> {
+    let mut out: Vec<(XmlElement<'s>, Option<usize>)> = Vec::new();
+    // indexes into `out` of the currently open tags.
+    let mut stack: Vec<usize> = Vec::new();
+    loop {
+      let offset = byte_offset_of(self.text, self.source).unwrap_or(0);
+      match self.parse_next() {
+        Some(el @ XmlElement::StartTag { .. }) => {
+          stack.push(out.len());
+          out.push((el, None));
+        }
+        Some(XmlElement::EndTag { name }) => {
+          let open = stack.iter().rposition(|&i| out[i].0 == name);
+          if let Some(pos) = open {
+            out[stack[pos]].1 = Some(offset);
+            stack.truncate(pos);
+          }
+        }
+        Some(XmlElement::Error(_)) | None => break,
+        Some(_) => (),
+      }
+    }
+    out.into_iter().filter_map(|(el, end)| Some((el, end?))).collect()
+  }
+
+  /// Makes an error for a problem with the element that starts at `at`.
+  fn error_at(&self, at: &'s str, kind: XmlErrorKind) -> XmlError {
+    let offset = byte_offset_of(at, self.source).unwrap_or(self.source.len());
+    XmlError { kind, offset }
+  }
+
+  /// Checks an element against the rules of strict mode.
+  fn strict_check(
+    &self, start: &'s str, el: &XmlElement<'s>,
+  ) -> Option<XmlErrorKind> {
+    match el {
+      XmlElement::Text(t) if self.depth == 0 && !t.trim().is_empty() => {
+        Some(XmlErrorKind::TextOutsideRoot)
+      }
+      XmlElement::CData(_) if self.depth == 0 => {
+        Some(XmlErrorKind::TextOutsideRoot)
+      }
+      XmlElement::Text(t) if t.contains("]]>") => {
+        Some(XmlErrorKind::CDataEndInText)
+      }
+      XmlElement::Comment(c)
+        if self.comment_terminators.is_none()
+          && (c.contains("--") || c.ends_with('-')) =>
+      {
+        Some(XmlErrorKind::MalformedComment)
+      }
+      XmlElement::ProcessingInstruction { target: "xml", .. }
+        if self.is_misplaced_declaration(start) =>
+      {
+        Some(XmlErrorKind::MisplacedDeclaration)
+      }
+      _ => None,
+    }
+  }
+
+  /// If an XML declaration starting at `start` isn't at the very start of the
+  /// input (ignoring leading whitespace).
+  fn is_misplaced_declaration(&self, start: &'s str) -> bool {
+    let leading = self.source.len() - self.source.trim_start().len();
+    byte_offset_of(start, self.source) != Some(leading)
+  }
+
+  /// Gets the next element, along with the input starting from that element.
+  fn next_with_start(&mut self) -> Option<(&'s str, XmlElement<'s>)> {
+    loop {
+      let start = self.text;
+      let mut el = self.parse_next()?;
+      if self.strict {
+        if let Some(kind) = self.strict_check(start, &el) {
+          el = XmlElement::Error(self.error_at(start, kind));
+        }
+      }
+      let skip = match el {
+        XmlElement::Error(_) => {
+          self.text = "";
+          if !self.strict {
+            return None;
+          }
+          false
+        }
+        XmlElement::StartTag { .. } => {
+          self.depth += 1;
+          false
+        }
+        XmlElement::EndTag { .. } => {
+          self.depth = self.depth.saturating_sub(1);
+          false
+        }
+        XmlElement::Text(t) => self.skip_whitespace && t.trim().is_empty(),
+        XmlElement::Comment(_) => self.skip_comments,
+        XmlElement::ProcessingInstruction { target: "xml", .. } => {
+          !self.keep_declaration && self.is_misplaced_declaration(start)
+        }
+        _ => false,
+      };
+      if !skip {
+        return Some((start, el));
+      }
+    }
+  }
+
+  /// The input that hasn't been processed yet.
+  pub(crate) fn remaining(&self) -> &'s str {
+    self.text
+  }
+
+  /// Parses the next element without any strict checking.
+  ///
+  /// Input that can't be parsed at all gives an `Error`, which `next` will
+  /// only pass along in strict mode.
+  pub(crate) fn parse_next(&mut self) -> Option<XmlElement<'s>> {
+    let start = self.text;
+    #[allow(clippy::never_loop)]
+    'clear_and_return_none: loop {
+      if self.text.is_empty() {
+        return None;
+      } else if let Some(body) = self.text.strip_prefix("<![CDATA[") {
+        let (cdata, rest) = match break_on_first_str(body, "]]>") {
+          Some(pair) => pair,
+          None => break 'clear_and_return_none,
+        };
+        self.text = rest;
+        return Some(XmlElement::CData(cdata));
+      } else if let Some(body) = self.text.strip_prefix("<?") {
+        let (pi, rest) = match break_on_first_str(body, "?>") {
+          Some(pair) => pair,
+          None => break 'clear_and_return_none,
+        };
+        self.text = rest;
+        let (target, data) = pi
+          .find(char::is_whitespace)
+          .map(|i| pi.split_at(i))
+          .unwrap_or((pi, ""));
+        let data = data.trim();
+        return Some(XmlElement::ProcessingInstruction { target, data });
+      } else if let Some(body) = self.text.strip_prefix("<!--") {
+        let found = match self.comment_terminators {
+          None => break_on_first_str(body, "-->"),
+          Some(terminators) => terminators
+            .iter()
+            .filter_map(|t| body.find(t).map(|i| (i, t.len())))
+            .min_by_key(|&(i, _)| i)
+            .map(|(i, len)| (&body[..i], &body[i + len..])),
+        };
+        let (comment, rest) = match found {
+          Some(pair) => pair,
+          // `<!--->` is never closed, the `-->` overlaps the `<!--`.
+          None if self.strict => {
+            self.text = "";
+            let kind = XmlErrorKind::MalformedComment;
+            return Some(XmlElement::Error(self.error_at(start, kind)));
+          }
+          None => break 'clear_and_return_none,
+        };
+        self.text = rest;
+        return Some(XmlElement::Comment(comment));
+      } else if self.text.starts_with('<') {
+        let end = find_tag_end(self.text, self.partial_input);
+        let (tag_text, rest) = match end {
+          Some(i) => (&self.text[1..i], &self.text[i + 1..]),
+          None => break 'clear_and_return_none,
+        };
+        self.text = rest;
+        // `<x/>` is empty, `</x>` is an end, anything else is a start.
+        let (body, is_empty, is_end) = match tag_text.strip_suffix('/') {
+          Some(body) => (body, true, false),
+          None => match tag_text.strip_prefix('/') {
+            Some(body) => (body, false, true),
+            None => (tag_text, false, false),
+          },
+        };
+        let leading_space = body.starts_with(char::is_whitespace);
+        if self.strict && leading_space && !body.trim().is_empty() {
+          let kind = XmlErrorKind::WhitespaceBeforeTagName;
+          return Some(XmlElement::Error(self.error_at(start, kind)));
+        }
+        // the name ends at whitespace, or at a `/` that's out of place.
+        let body = body.trim_start();
+        let name_end = body
+          .find(|c: char| c.is_whitespace() || c == '/')
+          .unwrap_or(body.len());
+        let (name, attrs) = body.split_at(name_end);
+        if self.strict && attrs.starts_with('/') && !name.is_empty() {
+          let kind = XmlErrorKind::MisplacedSlash;
+          return Some(XmlElement::Error(self.error_at(start, kind)));
+        }
+        let attrs = attrs.trim();
+        let tag = if is_empty {
+          XmlElement::EmptyTag { name, attrs }
+        } else if is_end {
+          if self.strict && !attrs.trim().is_empty() && !name.is_empty() {
+            let kind = XmlErrorKind::EndTagAttributes;
+            return Some(XmlElement::Error(self.error_at(start, kind)));
+          }
+          XmlElement::EndTag { name }
+        } else {
+          XmlElement::StartTag { name, attrs }
+        };
+        return Some(match tag {
+          XmlElement::StartTag { name, .. }
+          | XmlElement::EndTag { name }
+          | XmlElement::EmptyTag { name, .. }
+            if name.trim().is_empty() =>
+          {
+            XmlElement::Error(self.error_at(start, XmlErrorKind::EmptyTagName))
+          }
+          tag => tag,
+        });
+      } else {
+        let text_end_byte = match self.text_boundary {
+          None => find_byte(self.text.as_bytes(), b'<'),
+          Some(extra) => {
+            // a text element can start with the boundary, so look past it.
+            let skip =
+              if self.text.starts_with(extra) { extra.len_utf8() } else { 0 };
+            self.text[skip..].find(['<', extra]).map(|i| i + skip)
+          }
+        }
+        .unwrap_or(self.text.len());
+        let (here, rest) = self.text.split_at(text_end_byte);
+        self.text = rest;
+        return Some(XmlElement::Text(here));
+      }
+    }
+    self.text = "";
+    None
+  }
+}
+impl<'s> Iterator for ElementIterator<'s> {
+  type Item = XmlElement<'s>;
+
+  #[inline]
+  fn next(&mut self) -> Option<Self::Item> {
+    self.next_with_start().map(|(_start, el)| el)
+  }
+}
+impl<'s> core::iter::FusedIterator for ElementIterator<'s> {}
+
+/// Same as [`ElementIterator::new`].
+///
+/// ```rust
+/// # use magnesium::*;
+/// let mut iter: ElementIterator = "<a/>".into();
+/// let a = XmlElement::EmptyTag { name: "a", attrs: "" };
+/// assert_eq!(iter.next(), Some(a));
+/// ```
+impl<'s> From<&'s str> for ElementIterator<'s> {
+  #[inline]
+  fn from(text: &'s str) -> Self {
+    Self::new(text)
+  }
+}
+
+/// Iterator for [`ElementIterator::named`].
+#[derive(Debug, Clone)]
+pub struct Named<'s> {
+  iter: ElementIterator<'s>,
+  name: &'s str,
+}
+impl<'s> Iterator for Named<'s> {
+  type Item = XmlElement<'s>;
+
+  #[inline]
+  fn next(&mut self) -> Option<Self::Item> {
+    let name = self.name;
+    self.iter.find(|el| match el {
+      XmlElement::StartTag { name: n, .. }
+      | XmlElement::EmptyTag { name: n, .. } => *n == name,
+      _ => false,
+    })
+  }
+}
+impl<'s> core::iter::FusedIterator for Named<'s> {}
+
+/// Iterator for [`ElementIterator::indexed`].
+#[derive(Debug, Clone)]
+pub struct Indexed<'s> {
+  iter: ElementIterator<'s>,
+}
+impl<'s> Iterator for Indexed<'s> {
+  type Item = (usize, XmlElement<'s>);
+
+  #[inline]
+  fn next(&mut self) -> Option<Self::Item> {
+    let (start, el) = self.iter.next_with_start()?;
+    Some((byte_offset_of(start, self.iter.source).unwrap_or(0), el))
+  }
+}
+impl<'s> core::iter::FusedIterator for Indexed<'s> {}
+
+/// Iterator for [`ElementIterator::with_parent`].
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone)]
+pub struct WithParent<'s> {
+  iter: ElementIterator<'s>,
+  /// The names of the open tags, innermost last.
+  stack: Vec<&'s str>,
+}
+#[cfg(feature = "alloc")]
+impl<'s> Iterator for WithParent<'s> {
+  type Item = (Option<&'s str>, XmlElement<'s>);
+
+  #[inline]
+  fn next(&mut self) -> Option<Self::Item> {
+    let el = self.iter.next()?;
+    match el {
+      XmlElement::StartTag { name, .. } => {
+        let parent = self.stack.last().copied();
+        self.stack.push(name);
+        Some((parent, el))
+      }
+      XmlElement::EndTag { name } => {
+        if let Some(pos) = self.stack.iter().rposition(|&n| n == name) {
+          self.stack.truncate(pos);
+        }
+        Some((self.stack.last().copied(), el))
+      }
+      _ => Some((self.stack.last().copied(), el)),
+    }
+  }
+}
+#[cfg(feature = "alloc")]
+impl<'s> core::iter::FusedIterator for WithParent<'s> {}
+
+/// The names of the tags leading to an element.
+///
+/// Given by [`ElementIterator::with_path`].
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct ElementPath<'s> {
+  names: Vec<&'s str>,
+}
+#[cfg(feature = "alloc")]
+impl<'s> ElementPath<'s> {
+  /// The tag names, outermost first.
+  #[inline]
+  #[must_use]
+  pub fn names(&self) -> &[&'s str] {
+    &self.names
+  }
+
+  /// The last name in the path, if any.
+  #[inline]
+  #[must_use]
+  pub fn last(&self) -> Option<&'s str> {
+    self.names.last().copied()
+  }
+}
+#[cfg(feature = "alloc")]
+impl core::fmt::Display for ElementPath<'_> {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    for (i, name) in self.names.iter().enumerate() {
+      if i > 0 {
+        f.write_str("/")?;
+      }
+      f.write_str(name)?;
+    }
+    Ok(())
+  }
+}
+
+/// Iterator for [`ElementIterator::with_path`].
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone)]
+pub struct WithPath<'s> {
+  iter: ElementIterator<'s>,
+  /// The names of the open tags, innermost last.
+  stack: Vec<&'s str>,
+}
+#[cfg(feature = "alloc")]
+impl<'s> Iterator for WithPath<'s> {
+  type Item = (ElementPath<'s>, XmlElement<'s>);
+
+  #[inline]
+  fn next(&mut self) -> Option<Self::Item> {
+    let el = self.iter.next()?;
+    let names = match el {
+      XmlElement::StartTag { name, .. } => {
+        self.stack.push(name);
+        self.stack.clone()
+      }
+      XmlElement::EmptyTag { name, .. } => {
+        let mut names = self.stack.clone();
+        names.push(name);
+        names
+      }
+      XmlElement::EndTag { name } => {
+        match self.stack.iter().rposition(|&n| n == name) {
+          Some(pos) => {
+            let names = self.stack[..=pos].to_vec();
+            self.stack.truncate(pos);
+            names
+          }
+          None => {
+            let mut names = self.stack.clone();
+            names.push(name);
+            names
+          }
+        }
+      }
+      _ => self.stack.clone(),
+    };
+    Some((ElementPath { names }, el))
+  }
+}
+#[cfg(feature = "alloc")]
+impl<'s> core::iter::FusedIterator for WithPath<'s> {}
+
+/// Iterator for [`ElementIterator::with_parent_bounded`].
+#[derive(Debug, Clone)]
+pub struct WithParentBounded<'s, const N: usize> {
+  iter: ElementIterator<'s>,
+  /// The names of the outermost `N` open tags.
+  stack: [&'s str; N],
+  /// How many tags are open, which can be more than `N`.
+  depth: usize,
+}
+impl<'s, const N: usize> WithParentBounded<'s, N> {
+  /// How many tags are currently open.
+  ///
+  /// This keeps counting past `N`.
+  #[inline]
+  #[must_use]
+  pub fn depth(&self) -> usize {
+    self.depth
+  }
+
+  /// The innermost open tag, if it was recorded.
+  #[inline]
+  fn top(&self) -> Option<&'s str> {
+    if self.depth == 0 || self.depth > N {
+      None
+    } else {
+      Some(self.stack[self.depth - 1])
+    }
+  }
+}
+impl<'s, const N: usize> Iterator for WithParentBounded<'s, N> {
+  type Item = (Option<&'s str>, XmlElement<'s>);
+
+  #[inline]
+  fn next(&mut self) -> Option<Self::Item> {
+    let el = self.iter.next()?;
+    match el {
+      XmlElement::StartTag { name, .. } => {
+        let parent = self.top();
+        if self.depth < N {
+          self.stack[self.depth] = name;
+        }
+        self.depth += 1;
+        Some((parent, el))
+      }
+      XmlElement::EndTag { name } => {
+        if self.depth > N {
+          self.depth -= 1;
+        } else if let Some(pos) =
+          self.stack[..self.depth].iter().rposition(|&n| n == name)
+        {
+          self.depth = pos;
+        }
+        Some((self.top(), el))
+      }
+      _ => Some((self.top(), el)),
+    }
+  }
+}
+impl<'s, const N: usize> core::iter::FusedIterator
+  for WithParentBounded<'s, N>
+{
+}
+
+/// Iterator for [`ElementIterator::with_byte_ranges`].
+#[derive(Debug, Clone)]
+pub struct WithByteRanges<'s> {
+  iter: ElementIterator<'s>,
+}
+impl<'s> Iterator for WithByteRanges<'s> {
+  type Item = (core::ops::Range<usize>, XmlElement<'s>);
+
+  #[inline]
+  fn next(&mut self) -> Option<Self::Item> {
+    let (start, el) = self.iter.next_with_start()?;
+    let offset = byte_offset_of(start, self.iter.source).unwrap_or(0);
+    let len = start.len() - self.iter.text.len();
+    Some((offset..offset + len, el))
+  }
+}
+impl<'s> core::iter::FusedIterator for WithByteRanges<'s> {}
+
+/// Iterator for [`ElementIterator::validated`].
+#[derive(Debug, Clone)]
+pub struct Validated<'s, F> {
+  iter: ElementIterator<'s>,
+  f: F,
+}
+impl<'s, F, E> Iterator for Validated<'s, F>
+where
+  F: FnMut(&XmlElement<'s>) -> Result<(), E>,
+{
+  type Item = Result<XmlElement<'s>, E>;
+
+  #[inline]
+  fn next(&mut self) -> Option<Self::Item> {
+    let el = self.iter.next()?;
+    match (self.f)(&el) {
+      Ok(()) => Some(Ok(el)),
+      Err(e) => {
+        self.iter = ElementIterator::empty();
+        Some(Err(e))
+      }
+    }
+  }
+}
+impl<'s, F, E> core::iter::FusedIterator for Validated<'s, F> where
+  F: FnMut(&XmlElement<'s>) -> Result<(), E>
+{
+}
+
+/// Iterator for [`ElementIterator::map_tags`].
+#[derive(Debug, Clone)]
+pub struct MapTags<'s, F> {
+  iter: ElementIterator<'s>,
+  f: F,
+}
+impl<'s, F> Iterator for MapTags<'s, F>
+where
+  F: FnMut(&'s str) -> &'s str,
+{
+  type Item = XmlElement<'s>;
+
+  #[inline]
+  fn next(&mut self) -> Option<Self::Item> {
+    Some(match self.iter.next()? {
+      XmlElement::StartTag { name, attrs } => {
+        XmlElement::StartTag { name: (self.f)(name), attrs }
+      }
+      XmlElement::EndTag { name } => {
+        XmlElement::EndTag { name: (self.f)(name) }
+      }
+      XmlElement::EmptyTag { name, attrs } => {
+        XmlElement::EmptyTag { name: (self.f)(name), attrs }
+      }
+      other => other,
+    })
+  }
+}
+impl<'s, F> core::iter::FusedIterator for MapTags<'s, F> where
+  F: FnMut(&'s str) -> &'s str
+{
+}
+
+/// Configures an [`ElementIterator`] all in one place.
+///
+/// By default everything is off, so `build` gives the same iterator as
+/// [`ElementIterator::new`].
+///
+/// ```rust
+/// # use magnesium::*;
+/// let xml = "<a>\n  <!-- note -->\n  <b/>\n</a>";
+/// let mut iter = ElementIteratorBuilder::new()
+///   .skip_whitespace(true)
+///   .skip_comments(true)
+///   .build(xml);
+/// let a = XmlElement::StartTag { name: "a", attrs: "" };
+/// assert_eq!(iter.next(), Some(a));
+/// let b = XmlElement::EmptyTag { name: "b", attrs: "" };
+/// assert_eq!(iter.next(), Some(b));
+/// assert_eq!(iter.next(), Some(XmlElement::EndTag { name: "a" }));
+/// assert_eq!(iter.next(), None);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct ElementIteratorBuilder {
+  skip_whitespace: bool,
+  skip_comments: bool,
+  keep_declaration: bool,
+  strict: bool,
+}
+impl ElementIteratorBuilder {
+  /// Makes a new builder with everything off.
+  #[inline]
+  #[must_use]
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Skip `Text` elements that are only whitespace.
+  ///
+  /// This is like using [`skip_empty_text_elements`] with `filter_map`.
+  #[inline]
+  #[must_use]
+  pub fn skip_whitespace(self, skip_whitespace: bool) -> Self {
+    Self { skip_whitespace, ..self }
+  }
+
+  /// Skip all `Comment` elements.
+  ///
+  /// This is like using [`skip_comments`] with `filter_map`.
+  #[inline]
+  #[must_use]
+  pub fn skip_comments(self, skip_comments: bool) -> Self {
+    Self { skip_comments, ..self }
+  }
+
+  /// Give the XML declaration, if any, as a `ProcessingInstruction` element.
+  ///
+  /// This also keeps a declaration that's out of place, such as one after a
+  /// comment.
+  #[inline]
+  #[must_use]
+  pub fn keep_declaration(self, keep_declaration: bool) -> Self {
+    Self { keep_declaration, ..self }
+  }
+
+  /// Check the input like [`ElementIterator::new_strict`] does.
+  #[inline]
+  #[must_use]
+  pub fn strict(self, strict: bool) -> Self {
+    Self { strict, ..self }
+  }
+
+  /// Makes the configured iterator.
+  #[inline]
+  #[must_use]
+  pub fn build(self, text: &str) -> ElementIterator<'_> {
+    let body = if self.keep_declaration {
+      text.trim()
+    } else {
+      trim_xml_declaration(text).unwrap_or_default()
+    };
+    ElementIterator {
+      strict: self.strict,
+      skip_whitespace: self.skip_whitespace,
+      skip_comments: self.skip_comments,
+      keep_declaration: self.keep_declaration,
+      ..ElementIterator::with_body(text, body)
+    }
+  }
+}
+
+/// Iterator for [`ElementIterator::children_of`].
+#[derive(Debug)]
+pub struct ChildElements<'i, 's> {
+  iter: &'i mut ElementIterator<'s>,
+  name: &'i str,
+  /// How far within a child tag we are.
+  depth: usize,
+  /// If we've seen the close of the parent.
+  done: bool,
+}
+impl<'i, 's> Iterator for ChildElements<'i, 's> {
+  type Item = XmlElement<'s>;
+
+  #[inline]
+  fn next(&mut self) -> Option<Self::Item> {
+    while !self.done {
+      let el = self.iter.next()?;
+      match el {
+        XmlElement::StartTag { .. } => {
+          self.depth += 1;
+          if self.depth == 1 {
+            return Some(el);
+          }
+        }
+        XmlElement::EmptyTag { .. } | XmlElement::Error(_)
+          if self.depth == 0 =>
+        {
+          return Some(el)
+        }
+        XmlElement::EndTag { name } if self.depth == 0 => {
+          // a stray close that isn't for the parent is just skipped.
+          self.done = name == self.name;
+        }
+        XmlElement::EndTag { .. } => self.depth -= 1,
+        _ => (),
+      }
+    }
+    None
+  }
+}
+impl<'i, 's> core::iter::FusedIterator for ChildElements<'i, 's> {}
+
+/// Filters out `XmlElement::Text(t)` when `t` is only whitespace.
+///
+/// If `t` is more than just whitespace it is unaffected.
+///
+/// For use with [`filter_map`](core::iter::Iterator::filter_map) calls on
+/// an [`ElementIterator`].
+///
+/// ```rust
+/// # use magnesium::*;
+/// let iter = ElementIterator::new("").filter_map(skip_empty_text_elements);
+/// for element in iter {
+///   println!("{:?}", element);
+/// }
+/// ```
+///
+/// ## Failure
+/// * If the input is `XmlElement::Text` and the contained text becomes an empty
+///   string after calling [`trim`](str::trim).
+#[inline]
+#[must_use]
+pub fn skip_empty_text_elements(el: XmlElement<'_>) -> Option<XmlElement<'_>> {
+  match el {
+    XmlElement::Text(t) => {
+      if t.trim().is_empty() {
+        None
+      } else {
+        Some(XmlElement::Text(t))
+      }
+    }
+    other => Some(other),
+  }
+}
+
+/// Filters out `XmlElement::Comment(_)`.
+///
+/// For use with [`filter_map`](core::iter::Iterator::filter_map) calls on
+/// an [`ElementIterator`].
+///
+/// ```rust
+/// # use magnesium::*;
+/// let iter = ElementIterator::new("").filter_map(skip_comments);
+/// for element in iter {
+///   println!("{:?}", element);
+/// }
+/// ```
+///
+/// ## Failure
+/// * If the input is `XmlElement::Comment`.
+#[inline]
+#[must_use]
+pub fn skip_comments(el: XmlElement<'_>) -> Option<XmlElement<'_>> {
+  match el {
+    XmlElement::Comment(_) => None,
+    other => Some(other),
+  }
+}
+
+/// Makes a filter that keeps only the comments that `f` accepts.
+///
+/// For use with [`filter_map`](core::iter::Iterator::filter_map) calls on an
+/// [`ElementIterator`]. The text of each `Comment` is passed to `f`, and the
+/// comment is dropped unless `f` returns `true`. All other elements pass
+/// through.
+///
+/// ```rust
+/// # use magnesium::*;
+/// let xml = "<!-- license: MIT --><!-- todo --><a/>";
+/// let mut iter = ElementIterator::new(xml)
+///   .filter_map(keep_comments_where(|c| c.contains("license")));
+/// assert_eq!(iter.next(), Some(XmlElement::Comment(" license: MIT ")));
+/// let a = XmlElement::EmptyTag { name: "a", attrs: "" };
+/// assert_eq!(iter.next(), Some(a));
+/// ```
+#[inline]
+pub fn keep_comments_where<'s, F>(
+  f: F,
+) -> impl Fn(XmlElement<'s>) -> Option<XmlElement<'s>>
+where
+  F: Fn(&str) -> bool,
+{
+  move |el| match el {
+    XmlElement::Comment(c) if !f(c) => None,
+    other => Some(other),
+  }
+}
+
+/// Applies `str::trim` to a `Text` element. No effect otherwise.
+///
+/// For use with [`map`](core::iter::Iterator::map) calls on
+/// an [`ElementIterator`].
+///
+/// ```rust
+/// # use magnesium::*;
+/// let iter = ElementIterator::new("<a>  z</a>").map(trim_text);
+/// for element in iter {
+///   println!("{:?}", element);
+/// }
+/// ```
+#[inline]
+#[must_use]
+pub fn trim_text(el: XmlElement<'_>) -> XmlElement<'_> {
+  match el {
+    XmlElement::Text(t) => XmlElement::Text(t.trim()),
+    other => other,
+  }
+}
+
+/// Gets the inside of a value that's wrapped in a CDATA section.
+///
+/// If `text` starts with `<![CDATA[` and ends with `]]>` this gives the text
+/// in between, which is used as is (no decoding).
+///
+/// ```rust
+/// # use magnesium::*;
+/// assert_eq!(strip_cdata("<![CDATA[1 < 2]]>"), Some("1 < 2"));
+/// assert_eq!(strip_cdata("1 &lt; 2"), None);
+/// ```
+///
+/// ## Failure
+/// * If `text` isn't wrapped in a CDATA section.
+#[inline]
+#[must_use]
+pub fn strip_cdata(text: &str) -> Option<&str> {
+  text.strip_prefix("<![CDATA[")?.strip_suffix("]]>")
+}
+
+/// Gives `<a></a>` as a single `EmptyTag`, the same as `<a/>`.
+///
+/// When a `StartTag` is followed right away by its matching `EndTag` the pair
+/// is replaced with one `EmptyTag`, with the name and attributes of the
+/// `StartTag`. If `whitespace_is_empty` is set then a `Text` element of only
+/// whitespace between the two tags also counts as empty, and is dropped. All
+/// other elements pass through unchanged.
+///
+/// ```rust
+/// # use magnesium::*;
+/// let xml = "<a><b x='1'></b><c> </c></a>";
+/// let mut iter = coalesce_empty_elements(ElementIterator::new(xml), true);
+/// let a = XmlElement::StartTag { name: "a", attrs: "" };
+/// assert_eq!(iter.next(), Some(a));
+/// assert_eq!(
+///   iter.next(),
+///   Some(XmlElement::EmptyTag { name: "b", attrs: "x='1'" })
+/// );
+/// let c = XmlElement::EmptyTag { name: "c", attrs: "" };
+/// assert_eq!(iter.next(), Some(c));
+/// assert_eq!(iter.next(), Some(XmlElement::EndTag { name: "a" }));
+/// assert_eq!(iter.next(), None);
+/// ```
+#[inline]
+#[must_use]
+pub fn coalesce_empty_elements<'s, I>(
+  iter: I, whitespace_is_empty: bool,
+) -> CoalesceEmptyElements<'s, I::IntoIter>
+where
+  I: IntoIterator<Item = XmlElement<'s>>,
+{
+  CoalesceEmptyElements {
+    iter: iter.into_iter(),
+    whitespace_is_empty,
+    queue: [None, None],
+  }
+}
+
+/// Iterator for [`coalesce_empty_elements`].
+#[derive(Debug, Clone)]
+pub struct CoalesceEmptyElements<'s, I> {
+  iter: I,
+  whitespace_is_empty: bool,
+  /// Elements that were looked at but not used, in order.
+  queue: [Option<XmlElement<'s>>; 2],
+}
+impl<'s, I> CoalesceEmptyElements<'s, I>
+where
+  I: Iterator<Item = XmlElement<'s>>,
+{
+  /// Gets the next element, from the queue if there is one.
+  fn pull(&mut self) -> Option<XmlElement<'s>> {
+    match self.queue[0].take() {
+      Some(el) => {
+        self.queue[0] = self.queue[1].take();
+        Some(el)
+      }
+      None => self.iter.next(),
+    }
+  }
+
+  /// Puts an element back, to be given by the next `pull`.
+  fn push_front(&mut self, el: XmlElement<'s>) {
+    debug_assert!(self.queue[1].is_none());
+    self.queue[1] = self.queue[0].take();
+    self.queue[0] = Some(el);
+  }
+}
+impl<'s, I> Iterator for CoalesceEmptyElements<'s, I>
+where
+  I: Iterator<Item = XmlElement<'s>>,
+{
+  type Item = XmlElement<'s>;
+
+  #[inline]
+  fn next(&mut self) -> Option<Self::Item> {
+    let el = self.pull()?;
+    let (name, attrs) = match el {
+      XmlElement::StartTag { name, attrs } => (name, attrs),
+      other => return Some(other),
+    };
+    let mut between = None;
+    let mut after = self.pull();
+    if let Some(XmlElement::Text(t)) = after {
+      if self.whitespace_is_empty && t.trim().is_empty() {
+        between = after;
+        after = self.pull();
+      }
+    }
+    if let Some(XmlElement::EndTag { name: end }) = after {
+      if end == name {
+        return Some(XmlElement::EmptyTag { name, attrs });
+      }
+    }
+    if let Some(after) = after {
+      self.push_front(after);
+    }
+    if let Some(between) = between {
+      self.push_front(between);
+    }
+    Some(el)
+  }
+}
+
+/// Collects every element of the XML data into a `Vec`.
+///
+/// This is the same as calling `ElementIterator::new(xml).collect()`, and is
+/// intended as the entry point for snapshot (golden) testing of the parser's
+/// output.
+///
+/// ```rust
+/// # use magnesium::*;
+/// let elements = parse_all("<a>text</a>");
+/// assert_eq!(elements.len(), 3);
+/// assert_eq!(elements[1], XmlElement::Text("text"));
+/// ```
+#[cfg(feature = "alloc")]
+#[must_use]
+pub fn parse_all(xml: &str) -> Vec<XmlElement<'_>> {
+  ElementIterator::new(xml).collect()
+}
+
+/// Calls `f` on each element of the XML data, until it returns `Break`.
+///
+/// This is a "push" style alternative to using an [`ElementIterator`].
+///
+/// ```rust
+/// # use magnesium::*;
+/// use core::ops::ControlFlow;
+/// let mut names = Vec::new();
+/// visit_elements("<a><b/><c/></a>", |el| match el {
+///   XmlElement::EmptyTag { name, .. } => {
+///     names.push(name);
+///     ControlFlow::Break(())
+///   }
+///   _ => ControlFlow::Continue(()),
+/// });
+/// assert_eq!(names, ["b"]);
+/// ```
+#[inline]
+pub fn visit_elements<'s, F>(xml: &'s str, f: F)
+where
+  F: FnMut(XmlElement<'s>) -> core::ops::ControlFlow<()>,
+{
+  let _ = ElementIterator::new(xml).try_for_each(f);
+}
+
+/// Writes a series of elements as XML.
+///
+/// Each element is written with its [`Display`](core::fmt::Display) impl.
+/// Without `pretty` the elements are written one after the other with nothing
+/// added, so writing all the elements of an [`ElementIterator`] gives back the
+/// body of the input.
+///
+/// With `pretty`, each element goes on its own line, indented two spaces per
+/// level of tag nesting. Text is trimmed, and text that's only whitespace is
+/// skipped.
+///
+/// `Error` elements are skipped either way.
+///
+/// ```rust
+/// # use magnesium::*;
+/// let xml = "<a><b>text</b><c/></a>";
+/// let mut out = String::new();
+/// write_elements(&mut out, ElementIterator::new(xml), false).unwrap();
+/// assert_eq!(out, xml);
+///
+/// let mut out = String::new();
+/// write_elements(&mut out, ElementIterator::new(xml), true).unwrap();
+/// assert_eq!(out, "<a>\n  <b>\n    text\n  </b>\n  <c/>\n</a>");
+/// ```
+///
+/// ## Failure
+/// * If `out` gives an error.
+pub fn write_elements<'s, W, I>(
+  out: &mut W, iter: I, pretty: bool,
+) -> core::fmt::Result
+where
+  W: core::fmt::Write,
+  I: IntoIterator<Item = XmlElement<'s>>,
+{
+  let mut depth = 0_usize;
+  let mut first = true;
+  for el in iter {
+    let el = match el {
+      XmlElement::Error(_) => continue,
+      XmlElement::Text(t) if pretty => match t.trim() {
+        "" => continue,
+        t => XmlElement::Text(t),
+      },
+      el => el,
+    };
+    if pretty {
+      if let XmlElement::EndTag { .. } = el {
+        depth = depth.saturating_sub(1);
+      }
+      if !first {
+        out.write_char('\n')?;
+      }
+      for _ in 0..depth {
+        out.write_str("  ")?;
+      }
+      if let XmlElement::StartTag { .. } = el {
+        depth += 1;
+      }
+    }
+    first = false;
+    write!(out, "{}", el)?;
+  }
+  Ok(())
+}
+
+/// Gets the deepest nesting of tags within some XML data.
+///
+/// The root element is depth 1, its children are depth 2, and so on. An
+/// `EmptyTag` counts as a level just like a `StartTag` that's closed right
+/// away. If the input can't be parsed all the way through this still gives
+/// the deepest nesting seen before the problem.
+///
+/// ```rust
+/// # use magnesium::*;
+/// assert_eq!(max_depth("<a><b><c/></b><d/></a>"), 3);
+/// assert_eq!(max_depth("<a/>"), 1);
+/// assert_eq!(max_depth(""), 0);
+/// ```
+#[must_use]
+pub fn max_depth(xml: &str) -> usize {
+  let mut depth = 0_usize;
+  let mut max = 0;
+  for el in ElementIterator::new(xml) {
+    match el {
+      XmlElement::StartTag { .. } => {
+        depth += 1;
+        max = max.max(depth);
+      }
+      XmlElement::EmptyTag { .. } => max = max.max(depth + 1),
+      XmlElement::EndTag { .. } => depth = depth.saturating_sub(1),
+      _ => (),
+    }
+  }
+  max
+}
+
+/// Checks if some XML data has a root element.
+///
+/// This is `false` for a document that's empty or that only has things like
+/// comments, whitespace, and processing instructions. Only the input up to
+/// any parsing problem is checked.
+///
+/// ```rust
+/// # use magnesium::*;
+/// assert!(has_root_element("<?xml version=\"1.0\"?><root/>"));
+/// assert!(!has_root_element("<!-- nothing here -->"));
+/// ```
+#[must_use]
+pub fn has_root_element(xml: &str) -> bool {
+  ElementIterator::new(xml).any(|el| {
+    matches!(el, XmlElement::StartTag { .. } | XmlElement::EmptyTag { .. })
+  })
+}
+
+/// Splits XML data with several root elements into one slice per root.
+///
+/// This is for input that's a number of documents (or fragments) one after
+/// the other. Each slice goes from the start of a top level tag to the end of
+/// the tag that closes it, balancing any nested tags. Anything between the
+/// roots, such as whitespace or comments, is skipped.
+///
+/// If the input can't be parsed, or a root is never closed, the iteration
+/// ends at that point.
+///
+/// ```rust
+/// # use magnesium::*;
+/// let mut roots = split_top_level_roots("<a/>\n<b><c/></b>");
+/// assert_eq!(roots.next(), Some("<a/>"));
+/// assert_eq!(roots.next(), Some("<b><c/></b>"));
+/// assert_eq!(roots.next(), None);
+/// ```
+#[inline]
+#[must_use]
+pub fn split_top_level_roots(xml: &str) -> TopLevelRoots<'_> {
+  TopLevelRoots { iter: ElementIterator::new(xml) }
+}
+
+/// Iterator for [`split_top_level_roots`].
+#[derive(Debug, Clone)]
+pub struct TopLevelRoots<'s> {
+  iter: ElementIterator<'s>,
+}
+impl<'s> Iterator for TopLevelRoots<'s> {
+  type Item = &'s str;
+
+  #[inline]
+  fn next(&mut self) -> Option<Self::Item> {
+    next_subtree(&mut self.iter, false)
+  }
+}
+impl<'s> core::iter::FusedIterator for TopLevelRoots<'s> {}
+
+/// Iterates the raw XML of each direct child element of the root element.
+///
+/// Each slice goes from the start of a child tag to the end of the tag that
+/// closes it, balancing any nested tags, so it can be parsed on its own.
+/// Anything directly within the root that isn't an element, such as text or
+/// comments, is skipped. The iteration ends when the root closes. Any input
+/// after that is ignored.
+///
+/// ```rust
+/// # use magnesium::*;
+/// let xml = "<registry><types><type/></types>\n<enums/></registry>";
+/// let mut children = root_children(xml);
+/// assert_eq!(children.next(), Some("<types><type/></types>"));
+/// assert_eq!(children.next(), Some("<enums/>"));
+/// assert_eq!(children.next(), None);
+/// ```
+#[inline]
+#[must_use]
+pub fn root_children(xml: &str) -> RootChildren<'_> {
+  RootChildren { iter: ElementIterator::new(xml), in_root: false }
+}
+
+/// Iterator for [`root_children`].
+#[derive(Debug, Clone)]
+pub struct RootChildren<'s> {
+  iter: ElementIterator<'s>,
+  /// If the root element has been opened yet.
+  in_root: bool,
+}
+impl<'s> Iterator for RootChildren<'s> {
+  type Item = &'s str;
+
+  #[inline]
+  fn next(&mut self) -> Option<Self::Item> {
+    while !self.in_root {
+      match self.iter.parse_next()? {
+        XmlElement::StartTag { .. } => self.in_root = true,
+        XmlElement::EmptyTag { .. } | XmlElement::Error(_) => {
+          self.iter.text = "";
+          return None;
+        }
+        _ => (),
+      }
+    }
+    next_subtree(&mut self.iter, true)
+  }
+}
+impl<'s> core::iter::FusedIterator for RootChildren<'s> {}
+
+/// Gets the next balanced element (and everything within it) from `iter`.
+///
+/// If `stop_at_close` is set, an `EndTag` outside of any element ends the
+/// iteration (it's the parent closing), otherwise it's skipped.
+fn next_subtree<'s>(
+  iter: &mut ElementIterator<'s>, stop_at_close: bool,
+) -> Option<&'s str> {
+  let source = iter.source;
+  // unlike `bytes_consumed`, this doesn't count trailing whitespace.
+  let offset = |text: &str| byte_offset_of(text, source).unwrap_or(0);
+  let mut start = 0;
+  let mut depth = 0_usize;
+  loop {
+    let before = offset(iter.text);
+    match iter.parse_next()? {
+      XmlElement::StartTag { .. } => {
+        if depth == 0 {
+          start = before;
+        }
+        depth += 1;
+      }
+      XmlElement::EmptyTag { .. } if depth == 0 => {
+        return Some(&source[before..offset(iter.text)]);
+      }
+      XmlElement::EndTag { .. } if depth == 1 => {
+        return Some(&source[start..offset(iter.text)]);
+      }
+      XmlElement::EndTag { .. } if depth == 0 && stop_at_close => {
+        iter.text = "";
+        return None;
+      }
+      // a stray close outside of any element is just skipped.
+      XmlElement::EndTag { .. } => depth = depth.saturating_sub(1),
+      XmlElement::Error(_) => {
+        iter.text = "";
+        return None;
+      }
+      _ => (),
+    }
+  }
+}
+
+/// Gets the byte index up to which all the elements in `xml` are complete.
+///
+/// This is for parsing a buffer that's still growing, such as data arriving
+/// in chunks. Everything before the index can be parsed now, and everything
+/// after should be kept and have the next chunk appended to it.
+///
+/// * A tag, comment, CDATA section, or processing instruction that hasn't
+///   closed yet is incomplete.
+/// * Text that runs to the end of the data is also incomplete, since the next
+///   chunk might continue it.
+/// * An XML declaration at the start counts as complete once it closes.
+///
+/// ```rust
+/// # use magnesium::*;
+/// let xml = "<a><b/>text<c x=";
+/// let boundary = last_complete_boundary(xml);
+/// assert_eq!(&xml[..boundary], "<a><b/>text");
+/// ```
+#[must_use]
+pub fn last_complete_boundary(xml: &str) -> usize {
+  let mut iter = ElementIterator::new(xml).partial_input();
+  let mut boundary = byte_offset_of(iter.text, xml).unwrap_or(0);
+  loop {
+    match iter.parse_next() {
+      None | Some(XmlElement::Error(_)) => return boundary,
+      Some(XmlElement::Text(_)) if iter.text.is_empty() => return boundary,
+      Some(_) => boundary = byte_offset_of(iter.text, xml).unwrap_or(boundary),
+    }
+  }
+}
+
+/// Finds the byte index of the `>` that closes the tag at the start of `text`.
+///
+/// A `>` (or `/`) inside of a quoted attribute value doesn't end the tag. If
+/// the quotes never balance out this falls back to the first `>` seen. A `<`
+/// can't be in an attribute value, so a quote that's still open at a `<` is
+/// taken to be unbalanced right away. That keeps a tag with a stray quote from
+/// scanning the rest of the input.
+///
+/// When the text is only a `partial` prefix of the input, a quote that's still
+/// open at the end of the text might be closed by input that hasn't arrived
+/// yet, so the tag is incomplete (`None`) instead.
+fn find_tag_end(text: &str, partial: bool) -> Option<usize> {
+  let mut quote = None;
+  let mut first_gt = None;
+  for (i, b) in text.bytes().enumerate() {
+    if b == b'>' && first_gt.is_none() {
+      first_gt = Some(i);
+    }
+    match quote {
+      Some(q) if b == q => quote = None,
+      Some(_) if b == b'<' && i > 0 => {
+        return first_gt
+          .or_else(|| text[i..].bytes().position(|b| b == b'>').map(|p| i + p))
+      }
+      Some(_) => (),
+      None if b == b'"' || b == b'\'' => quote = Some(b),
+      None if b == b'>' => return Some(i),
+      None => (),
+    }
+  }
+  if partial {
+    None
+  } else {
+    first_gt
+  }
+}
+
+#[test]
+fn test_find_tag_end() {
+  assert_eq!(find_tag_end("<a>", false), Some(2));
+  assert_eq!(find_tag_end("<a", false), None);
+  assert_eq!(find_tag_end(r#"<a x="1>2">"#, false), Some(10));
+  assert_eq!(find_tag_end(r#"<a x='"'>"#, false), Some(8));
+  assert_eq!(find_tag_end(r#"<a x="/>"#, false), Some(7));
+  // unbalanced quotes
+  assert_eq!(find_tag_end(r#"<a x="1>"#, false), Some(7));
+  assert_eq!(find_tag_end(r#"<a x="1><b>"#, false), Some(7));
+  assert_eq!(find_tag_end(r#"<a x="1 <b>"#, false), Some(10));
+  assert_eq!(find_tag_end(r#"<a x="1 <b"#, false), None);
+  // a prefix of the input that ends within a quote is incomplete
+  assert_eq!(find_tag_end(r#"<a x="1>2"#, true), None);
+  assert_eq!(find_tag_end(r#"<a x="1>2">"#, true), Some(10));
+  assert_eq!(find_tag_end(r#"<a x="1><b>"#, true), Some(7));
+}
+
+/// Remove the XML declaration (and leading whitespace), if any.
+/// ## Failure
+/// * If the declaration opens but _doesn't_ close, this fails.
+fn trim_xml_declaration(mut text: &str) -> Option<&str> {
+  text = text.trim();
+  // the `xml` target must be the whole target, not `xml-stylesheet` etc.
+  let is_declaration = match text.strip_prefix("<?xml") {
+    Some(rest) => {
+      rest.is_empty()
+        || rest.starts_with(|c: char| c == '?' || c.is_whitespace())
+    }
+    None => false,
+  };
+  if is_declaration {
+    break_on_first_str(text.trim_start(), "?>")
+      .map(|(_decl, rest)| rest.trim_start())
+  } else {
+    Some(text)
+  }
+}
+
+#[test]
+fn test_trim_xml_declaration() {
+  assert_eq!(trim_xml_declaration(""), Some(""));
+
+  let pi = "<?xml-stylesheet href='a.css'?><a/>";
+  assert_eq!(trim_xml_declaration(pi), Some(pi));
 
   assert_eq!(trim_xml_declaration(" "), Some(""));
 
@@ -0,0 +1,80 @@
+use core::fmt;
+
+/// A problem with the XML data, found by a strict or checked iterator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct XmlError {
+  /// What the problem was.
+  pub kind: XmlErrorKind,
+  /// The byte offset of the problem within the input.
+  pub offset: usize,
+}
+impl fmt::Display for XmlError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{} at byte {}", self.kind, self.offset)
+  }
+}
+#[cfg(feature = "std")]
+impl std::error::Error for XmlError {}
+
+/// The kinds of [`XmlError`].
+///
+/// This enum is `#[non_exhaustive]`, so that new kinds of problem can be
+/// added without it being a breaking change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum XmlErrorKind {
+  /// There was non-whitespace text before the root element opened or after
+  /// it closed.
+  TextOutsideRoot,
+  /// A tag had no name, such as `<>` or `</>`.
+  EmptyTagName,
+  /// An attribute value's opening quote was never closed, such as `a="b`.
+  UnterminatedAttributeValue,
+  /// An attribute wasn't in the `key="value"` form at all, such as `a` or
+  /// `a=b`.
+  MalformedAttribute,
+  /// An end tag had something after its name, such as `</a b="1">`.
+  EndTagAttributes,
+  /// A tag had whitespace before its name, such as `< a>`.
+  WhitespaceBeforeTagName,
+  /// An element was too big to fit in a
+  /// [`CoreReadParser`](crate::CoreReadParser)'s buffer.
+  BufferOverflow,
+  /// The input wasn't valid UTF-8.
+  InvalidUtf8,
+  /// The input ended in the middle of an element.
+  UnexpectedEnd,
+  /// An XML declaration (`<?xml ...?>`) wasn't at the start of the input.
+  MisplacedDeclaration,
+  /// There was more input after the element that was expected, such as when
+  /// parsing a single [`XmlElement`](crate::XmlElement) from a `&str`.
+  TrailingContent,
+  /// A tag had a `/` after its name that wasn't at the end of the tag, such as
+  /// `<a/b>`.
+  MisplacedSlash,
+  /// Text outside of a CDATA section had a `]]>` in it, such as `a]]>b`.
+  CDataEndInText,
+  /// A comment was never closed, such as `<!--->`, or it had `--` within it or
+  /// ended with `-`.
+  MalformedComment,
+}
+impl fmt::Display for XmlErrorKind {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(match self {
+      Self::TextOutsideRoot => "text outside of the root element",
+      Self::EmptyTagName => "tag with an empty name",
+      Self::UnterminatedAttributeValue => "unterminated attribute value",
+      Self::MalformedAttribute => "malformed attribute",
+      Self::EndTagAttributes => "end tag with attributes",
+      Self::WhitespaceBeforeTagName => "whitespace before a tag name",
+      Self::BufferOverflow => "element too big for the buffer",
+      Self::InvalidUtf8 => "invalid UTF-8",
+      Self::UnexpectedEnd => "unexpected end of input",
+      Self::MisplacedDeclaration => "XML declaration not at the start",
+      Self::TrailingContent => "unexpected content after the element",
+      Self::MisplacedSlash => "a `/` in the middle of a tag",
+      Self::CDataEndInText => "`]]>` in text outside of CDATA",
+      Self::MalformedComment => "malformed comment",
+    })
+  }
+}
@@ -44,13 +44,54 @@ pub use elements::*;
 mod attributes;
 pub use attributes::*;
 
+mod declaration;
+pub use declaration::*;
+
+mod error;
+pub use error::*;
+
+mod decode;
+pub use decode::*;
+
+mod core_read;
+pub use core_read::*;
+
+mod names;
+pub use names::*;
+
+mod cursor;
+pub use cursor::*;
+
+mod scan;
+use scan::find_byte;
+
+#[cfg(feature="alloc")]
+mod namespaces;
+#[cfg(feature="alloc")]
+pub use namespaces::*;
+
+#[cfg(feature="alloc")]
+mod rewrite;
+#[cfg(feature="alloc")]
+pub use rewrite::*;
+
 #[cfg(feature="alloc")]
 extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+#[cfg(feature="alloc")]
+use alloc::borrow::Cow;
+#[cfg(feature="alloc")]
+use alloc::collections::BTreeMap;
 #[cfg(feature="alloc")]
 use alloc::string::String;
+#[cfg(feature="alloc")]
+use alloc::vec::Vec;
 
 /// Converts an escaped string to the intended text.
 ///
+/// This is [`decode_xml_with_policy`] using [`DecodePolicy::Strict`].
+///
 /// ```rust
 /// # use magnesium::revert_xml_encoding;
 /// assert_eq!("abc<", &revert_xml_encoding("abc&lt;"));
@@ -60,35 +101,9 @@ use alloc::string::String;
 /// ## Panics
 /// If an illegal '&' sequence is present.
 #[cfg(feature="alloc")]
+#[inline]
 pub fn revert_xml_encoding(text: &str) -> String {
-  let mut out = String::with_capacity(text.as_bytes().len());
-  let mut chars = text.chars();
-  while let Some(c) = chars.next() {
-    if c != '&' {
-      out.push(c);
-    } else {
-      match chars.next().unwrap() {
-        'l' => {
-          assert_eq!(chars.next().unwrap(), 't');
-          assert_eq!(chars.next().unwrap(), ';');
-          out.push('<');
-        }
-        'g' => {
-          assert_eq!(chars.next().unwrap(), 't');
-          assert_eq!(chars.next().unwrap(), ';');
-          out.push('>');
-        }
-        'a' => {
-          assert_eq!(chars.next().unwrap(), 'm');
-          assert_eq!(chars.next().unwrap(), 'p');
-          assert_eq!(chars.next().unwrap(), ';');
-          out.push('&');
-        }
-        other => panic!("unknown '&' char: {}", other),
-      }
-    }
-  }
-  out
+  decode_xml_with_policy(text, DecodePolicy::Strict)
 }
 
 /// Break the input around the first `c` found.
@@ -96,14 +111,12 @@ pub fn revert_xml_encoding(text: &str) -> String {
 /// Returns `(before, after)`.
 ///
 /// The `c` value isn't in _either_ of the return slices, it's discarded.
+#[inline]
 fn break_on_first_char(input: &str, c: char) -> Option<(&str, &str)> {
-  input.find(c).map(|b| {
-    let mut buf = [0_u8; 4];
-    let utf8_bytes_this_char = c.encode_utf8(&mut buf).len();
-    let (head, tail) = input.split_at(b);
-    let tail = &tail[utf8_bytes_this_char..];
-    (head, tail)
-  })
+  if c.is_ascii() {
+    return break_on_first_byte(input, c as u8);
+  }
+  input.find(c).map(|b| (&input[..b], &input[b + c.len_utf8()..]))
 }
 
 #[test]
@@ -114,6 +127,54 @@ fn test_break_on_first_char() {
   assert_eq!(break_on_first_char("a=b", '='), Some(("a", "b")));
 }
 
+/// Break the input around the first `byte` found, which must be ASCII.
+///
+/// Returns `(before, after)`.
+///
+/// This is the fast path of `break_on_first_char`. Any ASCII byte in a `str`
+/// is a whole `char`, so splitting around it is always on a char boundary.
+#[inline]
+fn break_on_first_byte(input: &str, byte: u8) -> Option<(&str, &str)> {
+  debug_assert!(byte.is_ascii());
+  find_byte(input.as_bytes(), byte).map(|i| (&input[..i], &input[i + 1..]))
+}
+
+#[test]
+fn test_break_on_first_byte() {
+  assert_eq!(break_on_first_byte("", b'='), None);
+  assert_eq!(break_on_first_byte("a", b'='), None);
+  assert_eq!(break_on_first_byte("a=", b'='), Some(("a", "")));
+  assert_eq!(break_on_first_byte("a=b=c", b'='), Some(("a", "b=c")));
+  assert_eq!(break_on_first_byte("ü=ß", b'='), Some(("ü", "ß")));
+}
+
+#[test]
+fn test_break_on_first_char_non_ascii() {
+  assert_eq!(break_on_first_char("aßb", 'ß'), Some(("a", "b")));
+  assert_eq!(break_on_first_char("ü→ü→", '→'), Some(("ü", "ü→")));
+  assert_eq!(break_on_first_char("abc", '→'), None);
+}
+
+#[test]
+fn test_break_on_first_char_matches_split_once() {
+  let fixtures = [
+    "",
+    "<p n=\"1\">Ünïcödé text — with &amp; entities, and ‘quotes’ → 1</p>",
+    "<enum value=\"0x0001\" name=\"GRAPHICS_ENUM_1\" comment=\"#1\"/>",
+    "ü→ü→ß=😀'\"<>",
+    "→",
+  ];
+  let chars = ['<', '>', '=', '"', '\'', 'ü', 'ß', '—', '→', '‘', '😀', 'x'];
+  for fixture in fixtures.iter() {
+    for start in fixture.char_indices().map(|(i, _)| i) {
+      let text = &fixture[start..];
+      for c in chars.iter() {
+        assert_eq!(break_on_first_char(text, *c), text.split_once(*c));
+      }
+    }
+  }
+}
+
 /// Break the input around the first `needle` found.
 ///
 /// Returns `(before, after)`.
@@ -138,3 +199,47 @@ fn test_break_on_first_str() {
   assert_eq!(break_on_first_str("a==", "=="), Some(("a", "")));
   assert_eq!(break_on_first_str("a==b", "=="), Some(("a", "b")));
 }
+
+/// Gets the byte offset of `sub` within `parent`.
+///
+/// This only works when `sub` is actually a slice of `parent`, otherwise it
+/// gives `None`. It compares where the two slices are in memory, not their
+/// contents, so it's a cheap way to find where an element's `name`, `attrs`,
+/// or text came from in the input. Every `&str` that an [`ElementIterator`]
+/// gives is a slice of the input it was made with.
+///
+/// ```rust
+/// # use magnesium::*;
+/// let xml = r#"<a x="1"><b/></a>"#;
+/// let el = ElementIterator::new(xml).next().unwrap();
+/// let (_, attrs) = el.unwrap_start_tag();
+/// let offset = byte_offset_of(attrs, xml).unwrap();
+/// assert_eq!(offset, 3);
+/// assert_eq!(&xml[offset..offset + attrs.len()], r#"x="1""#);
+/// assert_eq!(byte_offset_of("x", xml), None);
+/// ```
+///
+/// ## Failure
+/// * If `sub` isn't within `parent`. A `sub` that's in a different allocation
+///   (even with the same contents) can't be found. This includes the empty
+///   `attrs` of a tag without attributes, which might not point into the input.
+#[inline]
+#[must_use]
+pub fn byte_offset_of(sub: &str, parent: &str) -> Option<usize> {
+  let start = (sub.as_ptr() as usize).checked_sub(parent.as_ptr() as usize)?;
+  if start + sub.len() <= parent.len() {
+    Some(start)
+  } else {
+    None
+  }
+}
+
+#[test]
+fn test_byte_offset_of() {
+  let parent = "abcdef";
+  assert_eq!(byte_offset_of(parent, parent), Some(0));
+  assert_eq!(byte_offset_of(&parent[2..4], parent), Some(2));
+  assert_eq!(byte_offset_of(&parent[6..], parent), Some(6));
+  assert_eq!(byte_offset_of(&parent[..4], &parent[2..]), None);
+  assert_eq!(byte_offset_of(&parent[2..], &parent[..4]), None);
+}
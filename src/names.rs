@@ -0,0 +1,88 @@
+/// Checks if a processing instruction target is reserved by XML.
+///
+/// Targets that start with `xml` (in any case) are reserved for the XML
+/// standards, such as `xml-stylesheet` and `xml-model`. Any other target is
+/// for applications to use as they like.
+///
+/// ```rust
+/// # use magnesium::is_reserved_pi_target;
+/// assert!(is_reserved_pi_target("xml-stylesheet"));
+/// assert!(is_reserved_pi_target("XML-model"));
+/// assert!(!is_reserved_pi_target("php"));
+/// ```
+#[inline]
+#[must_use]
+pub fn is_reserved_pi_target(target: &str) -> bool {
+  target.len() >= 3 && target.as_bytes()[..3].eq_ignore_ascii_case(b"xml")
+}
+
+/// Checks if `s` is a valid XML name, for a tag or attribute key.
+///
+/// This follows the `Name` rule of the XML spec: the first character must be
+/// a letter, `_`, or `:` (or one of many non-ASCII characters), and the rest
+/// can also be digits, `-`, `.`, and a few others. An empty string is not a
+/// valid name.
+///
+/// ```rust
+/// # use magnesium::is_valid_xml_name;
+/// assert!(is_valid_xml_name("data-foo.bar"));
+/// assert!(is_valid_xml_name("xlink:href"));
+/// assert!(!is_valid_xml_name("1abc"));
+/// assert!(!is_valid_xml_name("a b"));
+/// ```
+#[must_use]
+pub fn is_valid_xml_name(s: &str) -> bool {
+  let mut chars = s.chars();
+  match chars.next() {
+    Some(c) if is_name_start_char(c) => chars.all(is_name_char),
+    _ => false,
+  }
+}
+
+/// Checks if `s` is a valid XML name token (`Nmtoken`).
+///
+/// A name token is like a name (see [`is_valid_xml_name`]), except that any
+/// name character can come first, so `1abc` and `-x` are name tokens. This is
+/// what attribute values of type `NMTOKEN` must be. An empty string is not a
+/// valid name token.
+///
+/// ```rust
+/// # use magnesium::is_nmtoken;
+/// assert!(is_nmtoken("1abc"));
+/// assert!(is_nmtoken("GL_FLOAT"));
+/// assert!(!is_nmtoken(""));
+/// assert!(!is_nmtoken("a b"));
+/// ```
+#[must_use]
+pub fn is_nmtoken(s: &str) -> bool {
+  !s.is_empty() && s.chars().all(is_name_char)
+}
+
+/// The `NameStartChar` rule of the XML spec.
+fn is_name_start_char(c: char) -> bool {
+  matches!(c,
+    ':' | 'A'..='Z' | '_' | 'a'..='z'
+    | '\u{C0}'..='\u{D6}'
+    | '\u{D8}'..='\u{F6}'
+    | '\u{F8}'..='\u{2FF}'
+    | '\u{370}'..='\u{37D}'
+    | '\u{37F}'..='\u{1FFF}'
+    | '\u{200C}'..='\u{200D}'
+    | '\u{2070}'..='\u{218F}'
+    | '\u{2C00}'..='\u{2FEF}'
+    | '\u{3001}'..='\u{D7FF}'
+    | '\u{F900}'..='\u{FDCF}'
+    | '\u{FDF0}'..='\u{FFFD}'
+    | '\u{10000}'..='\u{EFFFF}'
+  )
+}
+
+/// The `NameChar` rule of the XML spec.
+fn is_name_char(c: char) -> bool {
+  is_name_start_char(c)
+    || matches!(c,
+      '-' | '.' | '0'..='9' | '\u{B7}'
+      | '\u{300}'..='\u{36F}'
+      | '\u{203F}'..='\u{2040}'
+    )
+}
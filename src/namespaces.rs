@@ -0,0 +1,106 @@
+use super::*;
+
+/// The namespace that the `xml` prefix is always bound to.
+const XML_NAMESPACE: &str = "http://www.w3.org/XML/1998/namespace";
+
+/// Tracks the `xmlns` declarations that are in scope as you walk some XML.
+///
+/// Pass each element from an [`ElementIterator`] to [`feed`](Self::feed), and
+/// then [`resolve`](Self::resolve) will look up a prefix using the
+/// declarations of the current scope.
+///
+/// * Declarations on a `StartTag` stay in scope until the matching `EndTag`.
+/// * Declarations on an `EmptyTag` stay in scope only until the next element is
+///   fed in, so they can be used to resolve the empty tag's own name.
+///
+/// ```rust
+/// # use magnesium::*;
+/// let xml = r#"<a xmlns:x="urn:one"><b xmlns:x="urn:two"/></a>"#;
+/// let mut resolver = NamespaceResolver::new();
+/// let mut iter = ElementIterator::new(xml);
+/// resolver.feed(&iter.next().unwrap());
+/// assert_eq!(resolver.resolve("x"), Some("urn:one"));
+/// resolver.feed(&iter.next().unwrap());
+/// assert_eq!(resolver.resolve("x"), Some("urn:two"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct NamespaceResolver<'s> {
+  /// `(prefix, uri)` pairs, the default namespace has an empty prefix.
+  bindings: Vec<(&'s str, &'s str)>,
+  /// The length of `bindings` at the start of each open scope.
+  scopes: Vec<usize>,
+  /// If the last element fed in was an `EmptyTag` its scope is still open.
+  pop_pending: bool,
+}
+impl<'s> NamespaceResolver<'s> {
+  /// Makes a new resolver with no declarations in scope.
+  #[inline]
+  #[must_use]
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Updates the scope using the next element of the XML.
+  ///
+  /// Elements other than tags don't affect the scope.
+  pub fn feed(&mut self, el: &XmlElement<'s>) {
+    if self.pop_pending {
+      self.pop_scope();
+      self.pop_pending = false;
+    }
+    match *el {
+      XmlElement::StartTag { attrs, .. } => self.push_scope(attrs),
+      XmlElement::EmptyTag { attrs, .. } => {
+        self.push_scope(attrs);
+        self.pop_pending = true;
+      }
+      XmlElement::EndTag { .. } => self.pop_scope(),
+      _ => (),
+    }
+  }
+
+  /// Gets the namespace URI bound to the `prefix` in the current scope.
+  ///
+  /// Use an empty prefix to look up the default namespace. The `xml` prefix
+  /// is always bound, even without a declaration.
+  ///
+  /// If the default namespace was undeclared with `xmlns=""` it resolves to
+  /// `None`.
+  #[must_use]
+  pub fn resolve(&self, prefix: &str) -> Option<&'s str> {
+    match self.bindings.iter().rev().find(|(p, _)| *p == prefix) {
+      Some((_, "")) => None,
+      Some((_, uri)) => Some(uri),
+      None if prefix == "xml" => Some(XML_NAMESPACE),
+      None => None,
+    }
+  }
+
+  /// Gets the namespace URI of a (possibly prefixed) tag name.
+  ///
+  /// A name like `x:tag` resolves the `x` prefix, and a name without a prefix
+  /// is in the default namespace.
+  #[must_use]
+  pub fn resolve_name(&self, name: &str) -> Option<&'s str> {
+    match break_on_first_char(name, ':') {
+      Some((prefix, _local)) => self.resolve(prefix),
+      None => self.resolve(""),
+    }
+  }
+
+  fn push_scope(&mut self, attrs: &'s str) {
+    self.scopes.push(self.bindings.len());
+    for attr in TagAttributeIterator::new(attrs) {
+      if attr.is_namespace_declaration() {
+        let prefix = attr.namespace_prefix().unwrap_or("");
+        self.bindings.push((prefix, attr.value));
+      }
+    }
+  }
+
+  fn pop_scope(&mut self) {
+    if let Some(len) = self.scopes.pop() {
+      self.bindings.truncate(len);
+    }
+  }
+}
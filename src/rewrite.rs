@@ -0,0 +1,238 @@
+use super::*;
+
+/// An element that might have had its text content rewritten.
+///
+/// Adapters that change the text of an element can't always give back a
+/// borrowed [`XmlElement`], so they give back one of these instead.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum CowElement<'s> {
+  /// Text content, which is owned if it had to be changed.
+  Text(Cow<'s, str>),
+
+  /// A comment, which is owned if it had to be changed.
+  Comment(Cow<'s, str>),
+
+  /// Any element that was passed along unchanged.
+  Other(XmlElement<'s>),
+}
+impl<'s> From<XmlElement<'s>> for CowElement<'s> {
+  #[inline]
+  fn from(el: XmlElement<'s>) -> Self {
+    Self::Other(el)
+  }
+}
+
+/// Merges each run of adjacent `Text` and `CData` elements into one `Text`.
+///
+/// The text of `Text` elements is decoded with [`decode_xml_lossy`], so any
+/// entity that can't be decoded is kept as is, and the text of `CData`
+/// elements is used as is. All other elements pass through unchanged and end
+/// any run of text in progress.
+///
+/// ```rust
+/// # use magnesium::*;
+/// let xml = "<a>1 &lt; <![CDATA[<2>]]></a>";
+/// let mut iter = coalesce_text(ElementIterator::new(xml)).skip(1);
+/// assert_eq!(iter.next(), Some(CowElement::Text("1 < <2>".into())));
+/// ```
+#[inline]
+#[must_use]
+pub fn coalesce_text<'s, I>(iter: I) -> CoalesceText<'s, I::IntoIter>
+where
+  I: IntoIterator<Item = XmlElement<'s>>,
+{
+  CoalesceText { iter: iter.into_iter(), pending: None }
+}
+
+/// Iterator for [`coalesce_text`].
+#[derive(Debug, Clone)]
+pub struct CoalesceText<'s, I> {
+  iter: I,
+  /// A non-text element that ended the previous run.
+  pending: Option<XmlElement<'s>>,
+}
+impl<'s, I> Iterator for CoalesceText<'s, I>
+where
+  I: Iterator<Item = XmlElement<'s>>,
+{
+  type Item = CowElement<'s>;
+
+  #[inline]
+  fn next(&mut self) -> Option<Self::Item> {
+    let mut run: Option<Cow<'s, str>> = None;
+    loop {
+      let piece = match self.pending.take().or_else(|| self.iter.next()) {
+        Some(XmlElement::Text(t)) if t.contains('&') => {
+          Cow::Owned(decode_xml_lossy(t))
+        }
+        Some(XmlElement::Text(t)) | Some(XmlElement::CData(t)) => {
+          Cow::Borrowed(t)
+        }
+        Some(other) if run.is_some() => {
+          self.pending = Some(other);
+          return run.map(CowElement::Text);
+        }
+        Some(other) => return Some(CowElement::Other(other)),
+        None => return run.map(CowElement::Text),
+      };
+      run = Some(match run {
+        None => piece,
+        Some(r) => Cow::Owned(r.into_owned() + &piece),
+      });
+    }
+  }
+}
+
+/// Gets all the text of some XML data, joined together.
+///
+/// This concatenates every `Text` element (decoded like [`decode_xml_lossy`],
+/// keeping any entity that can't be decoded as is) and every `CData` element
+/// (as is). Tags, comments, and everything else are ignored. If
+/// `skip_whitespace` is set then `Text` that's only whitespace is left out.
+///
+/// ```rust
+/// # use magnesium::*;
+/// let xml = "<p>\n  <b>1 &lt;</b> <![CDATA[<2>]]>\n</p>";
+/// assert_eq!(all_text(xml, true), "1 <<2>");
+/// assert_eq!(all_text(xml, false), "\n  1 < <2>\n");
+/// ```
+#[must_use]
+pub fn all_text(xml: &str, skip_whitespace: bool) -> String {
+  let mut out = String::new();
+  for el in ElementIterator::new(xml) {
+    match el {
+      XmlElement::Text(t) if skip_whitespace && t.trim().is_empty() => (),
+      XmlElement::Text(t) => push_decoded(&mut out, t, None),
+      XmlElement::CData(t) => out.push_str(t),
+      _ => (),
+    }
+  }
+  out
+}
+
+/// Collapses each run of whitespace within text to a single space.
+///
+/// This is a [`filter_map`](core::iter::Iterator::filter_map) helper, like
+/// HTML's normal handling of whitespace. The text of `Text` elements has each
+/// internal run of whitespace replaced with one space, and is trimmed at both
+/// ends. If that leaves the text empty the element is dropped. Text that's
+/// already collapsed stays borrowed. All other elements pass through.
+///
+/// This accepts either an [`XmlElement`] or a [`CowElement`], so it can follow
+/// other adapters such as [`coalesce_text`].
+///
+/// ```rust
+/// # use magnesium::*;
+/// let xml = "<p>  a\n\t b  </p>";
+/// let mut iter = ElementIterator::new(xml).filter_map(collapse_whitespace);
+/// let _p = iter.next();
+/// assert_eq!(iter.next(), Some(CowElement::Text("a b".into())));
+/// ```
+///
+/// ## Failure
+/// * If the text is only whitespace.
+#[must_use]
+pub fn collapse_whitespace<'s, E>(el: E) -> Option<CowElement<'s>>
+where
+  E: Into<CowElement<'s>>,
+{
+  let text = match el.into() {
+    CowElement::Other(XmlElement::Text(t)) => Cow::Borrowed(t),
+    CowElement::Text(t) => t,
+    other => return Some(other),
+  };
+  let text = collapse_runs(text);
+  if text.is_empty() {
+    None
+  } else {
+    Some(CowElement::Text(text))
+  }
+}
+
+/// Collapses each run of whitespace within a comment to a single space.
+///
+/// This is a [`filter_map`](core::iter::Iterator::filter_map) helper, for
+/// comparing comments (such as license headers) without caring how they were
+/// wrapped or indented. The text of `Comment` elements has each internal run
+/// of whitespace replaced with one space, and is trimmed at both ends. Unlike
+/// with [`collapse_whitespace`], a comment that's left empty is kept. A
+/// comment that's already collapsed stays borrowed. All other elements pass
+/// through.
+///
+/// ```rust
+/// # use magnesium::*;
+/// let xml = "<!--\n  Copyright 2021\n  All rights reserved.\n--><a/>";
+/// let mut iter =
+///   ElementIterator::new(xml).filter_map(normalize_comment_whitespace);
+/// assert_eq!(
+///   iter.next(),
+///   Some(CowElement::Comment("Copyright 2021 All rights reserved.".into()))
+/// );
+/// ```
+///
+/// ## Failure
+/// * Never, this always gives `Some`. It returns an `Option` so that it can be
+///   used with `filter_map` alongside the other helpers.
+#[must_use]
+pub fn normalize_comment_whitespace<'s, E>(el: E) -> Option<CowElement<'s>>
+where
+  E: Into<CowElement<'s>>,
+{
+  let comment = match el.into() {
+    CowElement::Other(XmlElement::Comment(c)) => Cow::Borrowed(c),
+    CowElement::Comment(c) => c,
+    other => return Some(other),
+  };
+  Some(CowElement::Comment(collapse_runs(comment)))
+}
+
+/// Replaces each run of whitespace with one space, and trims both ends.
+///
+/// Text that's already like that is given back unchanged.
+fn collapse_runs(text: Cow<'_, str>) -> Cow<'_, str> {
+  let words = text.split_whitespace();
+  let words_len: usize =
+    words.clone().map(|w| w.len() + 1).sum::<usize>().saturating_sub(1);
+  let collapsed = words_len == text.len()
+    && !text.contains(|c: char| c.is_whitespace() && c != ' ');
+  if collapsed {
+    text
+  } else {
+    let mut out = String::with_capacity(words_len);
+    for w in words {
+      if !out.is_empty() {
+        out.push(' ');
+      }
+      out.push_str(w);
+    }
+    Cow::Owned(out)
+  }
+}
+
+/// Gets the decoded text within a piece of XML, such as a mixed-content
+/// element.
+///
+/// This is for something like the output of [`ElementIterator::inner_xml`].
+/// `Text` is decoded as with [`decode_xml_lossy`], while the inside of a CDATA
+/// section is used literally (never decoded). All the text is kept, including
+/// whitespace at the start or end. Tags, comments, and everything else are
+/// ignored.
+///
+/// ```rust
+/// # use magnesium::*;
+/// let xml = "<p>1 &lt; <b>2</b> <![CDATA[&lt;3]]></p>";
+/// assert_eq!(decode_element_text(xml), "1 < 2 &lt;3");
+/// ```
+#[must_use]
+pub fn decode_element_text(xml_subtree: &str) -> String {
+  let mut out = String::with_capacity(xml_subtree.len());
+  let mut iter = ElementIterator::with_body(xml_subtree, xml_subtree);
+  while let Some(el) = iter.parse_next() {
+    match el {
+      XmlElement::Text(t) => push_decoded(&mut out, t, None),
+      XmlElement::CData(t) => out.push_str(t),
+      _ => (),
+    }
+  }
+  out
+}
@@ -0,0 +1,76 @@
+//! Searching for delimiter bytes.
+//!
+//! With the `memchr` feature the searches look at a whole `usize` of input at
+//! a time, otherwise they go byte by byte. Both give the same answer, only the
+//! speed is different.
+
+/// Finds the index of the first `needle` byte in `haystack`.
+#[inline]
+pub(crate) fn find_byte(haystack: &[u8], needle: u8) -> Option<usize> {
+  #[cfg(feature = "memchr")]
+  {
+    find_byte_wide(haystack, needle)
+  }
+  #[cfg(not(feature = "memchr"))]
+  {
+    find_byte_simple(haystack, needle)
+  }
+}
+
+/// Checks one byte at a time.
+#[cfg_attr(feature = "memchr", allow(dead_code))]
+#[inline]
+fn find_byte_simple(haystack: &[u8], needle: u8) -> Option<usize> {
+  haystack.iter().position(|&b| b == needle)
+}
+
+/// Checks a `usize` worth of bytes at a time, then finishes byte by byte.
+#[cfg_attr(not(feature = "memchr"), allow(dead_code))]
+fn find_byte_wide(haystack: &[u8], needle: u8) -> Option<usize> {
+  const WIDTH: usize = core::mem::size_of::<usize>();
+  /// `0x01` in every byte.
+  const LO: usize = usize::MAX / 255;
+  /// `0x80` in every byte.
+  const HI: usize = LO * 0x80;
+  let repeated = LO * usize::from(needle);
+  let mut i = 0;
+  while let Some(chunk) = haystack.get(i..i + WIDTH) {
+    let mut bytes = [0_u8; WIDTH];
+    bytes.copy_from_slice(chunk);
+    // any byte equal to `needle` becomes zero, and that sets its high bit.
+    let x = usize::from_ne_bytes(bytes) ^ repeated;
+    if x.wrapping_sub(LO) & !x & HI != 0 {
+      break;
+    }
+    i += WIDTH;
+  }
+  find_byte_simple(&haystack[i..], needle).map(|p| i + p)
+}
+
+#[test]
+fn test_find_byte_agrees() {
+  let samples: [&[u8]; 8] = [
+    b"",
+    b"<",
+    b"abc",
+    b"text with no tag at all, long enough for a few words",
+    b"0123456789abcdef<",
+    b"<registry><enums group=\"x\"><enum value=\"0x0\"/></enums></registry>",
+    "ünïcödé → text <b>".as_bytes(),
+    &[0x80, 0xFF, 0x7F, 0x3C, 0x3D, 0xBC, 0x3E, 0, 1, 2, 0x3C],
+  ];
+  for sample in samples.iter() {
+    for start in 0..sample.len() {
+      let hay = &sample[start..];
+      for needle in [b'<', b'>', b'=', b'"', 0, 0x80, 0xFF].iter() {
+        assert_eq!(
+          find_byte_wide(hay, *needle),
+          find_byte_simple(hay, *needle),
+          "{:?} {}",
+          hay,
+          needle
+        );
+      }
+    }
+  }
+}
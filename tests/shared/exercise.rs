@@ -0,0 +1,76 @@
+// The body of the no-panic checks, shared by `tests/test_no_panic.rs` and the
+// `cargo fuzz` target so that they both cover the same things. Each of them
+// brings this in with `include!`.
+
+/// Runs the iterators (and things built on them) all the way through.
+fn exercise(xml: &str) {
+  ElementIterator::new(xml).for_each(drop);
+  ElementIterator::new_strict(xml).for_each(drop);
+  ElementIteratorBuilder::new()
+    .keep_declaration(true)
+    .strict(true)
+    .build(xml)
+    .for_each(drop);
+  ElementIterator::new(xml).with_byte_ranges().for_each(drop);
+  ElementIterator::new(xml).with_parent_bounded::<2>().for_each(drop);
+  coalesce_empty_elements(ElementIterator::new(xml), true).for_each(drop);
+  coalesce_empty_elements(ElementIterator::new_strict(xml), false)
+    .for_each(drop);
+
+  let mut iter = ElementIterator::new(xml);
+  while let Some(el) = iter.next() {
+    if let XmlElement::StartTag { name, attrs }
+    | XmlElement::EmptyTag { name, attrs } = el
+    {
+      TagAttributeIterator::new(attrs).for_each(drop);
+      TagAttributeIterator::new(attrs).with_spans().for_each(drop);
+      let mut attrs = TagAttributeIterator::new(attrs);
+      while attrs.try_next().is_some() {}
+      if el.is_start() {
+        iter.clone().children_of(name).for_each(drop);
+        let _ = iter.clone().inner_xml(name);
+        let _ = iter.clone().skip_element(name);
+      }
+    }
+  }
+  let mut iter = ElementIterator::new(xml);
+  while iter.find_element(XmlElement::is_start).is_some() {}
+  iter.reset();
+  iter.for_each(drop);
+
+  let mut cursor = XmlCursor::new(xml);
+  while cursor.peek().is_some() {
+    let _ = cursor.bump();
+    cursor.current_attributes().for_each(drop);
+  }
+
+  TagAttributeIterator::new(xml).for_each(drop);
+  TagAttributeIterator::new_lenient(xml).with_spans().for_each(drop);
+  let mut attrs = TagAttributeIterator::new(xml);
+  while attrs.try_next().is_some() {}
+
+  let _ = write_decoded(&mut String::new(), xml);
+  split_top_level_roots(xml).for_each(drop);
+  let _ = last_complete_boundary(xml);
+
+  for chunk_size in [1, 7, xml.len().max(1)].iter() {
+    let mut buffer = [0_u8; 64];
+    let mut parser = CoreReadParser::new(&mut buffer);
+    let fed = xml
+      .as_bytes()
+      .chunks(*chunk_size)
+      .all(|c| parser.feed(c, |_| ()).is_ok());
+    if fed {
+      let _ = parser.finish(|_| ());
+    }
+  }
+
+  #[cfg(feature = "alloc")]
+  {
+    let _ = decode_xml_lossy(xml);
+    let _ = ElementIterator::new(xml).zip_with_end_tags();
+    ElementIterator::new(xml).with_parent().for_each(drop);
+    ElementIterator::new(xml).with_path().for_each(drop);
+    let _ = parse_internal_entities(xml);
+  }
+}
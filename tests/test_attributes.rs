@@ -38,3 +38,499 @@ fn test_parsing() {
   );
   assert_eq!(iter.next(), None);
 }
+
+#[test]
+fn test_value_bytes() {
+  let attr = TagAttribute { key: "name", value: "GRAPHIC" };
+  assert_eq!(attr.value_bytes(), b"GRAPHIC");
+}
+
+#[test]
+fn test_decode_hex_into() {
+  let attr = Tais::new(r#"value="01ff""#).next().unwrap();
+  let mut buf = [0_u8; 2];
+  assert_eq!(attr.decode_hex_into(&mut buf), Some(2));
+  assert_eq!(buf, [0x01, 0xff]);
+
+  let attr = TagAttribute { key: "value", value: "0xABcd" };
+  assert_eq!(attr.decode_hex_into(&mut buf), Some(2));
+  assert_eq!(buf, [0xab, 0xcd]);
+
+  // odd digit count
+  let attr = TagAttribute { key: "value", value: "abc" };
+  assert_eq!(attr.decode_hex_into(&mut buf), None);
+
+  // not hex
+  let attr = TagAttribute { key: "value", value: "zz" };
+  assert_eq!(attr.decode_hex_into(&mut buf), None);
+
+  // buffer too small
+  let attr = TagAttribute { key: "value", value: "010203" };
+  assert_eq!(attr.decode_hex_into(&mut buf), None);
+}
+
+#[test]
+fn test_decode_value_into() {
+  let attr = Tais::new(r#"a="&#65;&lt;&#x42;""#).next().unwrap();
+  let mut buf = [0_u8; 4];
+  assert_eq!(attr.decode_value_into(&mut buf), Ok(3));
+  assert_eq!(&buf[..3], b"A<B");
+
+  let attr = TagAttribute { key: "a", value: "\u{e9}&#xe9;" };
+  assert_eq!(attr.decode_value_into(&mut buf), Ok(4));
+  assert_eq!(&buf[..4], "\u{e9}\u{e9}".as_bytes());
+
+  // buffer too small
+  let attr = TagAttribute { key: "a", value: "abc&#65;" };
+  let err = DecodeError { kind: DecodeErrorKind::Write, offset: 3 };
+  assert_eq!(attr.decode_value_into(&mut buf[..3]), Err(err));
+
+  let attr = TagAttribute { key: "a", value: "a&bogus;" };
+  let err = DecodeError { kind: DecodeErrorKind::UnknownEntity, offset: 1 };
+  assert_eq!(attr.decode_value_into(&mut buf), Err(err));
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn test_to_btree_map() {
+  let iter =
+    Tais::new(r#"name="GRAPHIC_POINTS" value="0x0000" group="Polygon""#);
+  let map = iter.to_btree_map();
+  assert_eq!(map.len(), 3);
+  assert_eq!(map.get("name"), Some(&"GRAPHIC_POINTS"));
+  assert_eq!(map.get("group"), Some(&"Polygon"));
+  assert_eq!(map.get("ferris"), None);
+
+  // last one wins
+  let map = Tais::new(r#"a="1" a="2""#).to_btree_map();
+  assert_eq!(map.get("a"), Some(&"2"));
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn test_new_decoding() {
+  let mut iter = Tais::new_decoding(r#"x="a&amp;b" y="plain""#);
+  let x = iter.next().unwrap();
+  assert_eq!(x.key, "x");
+  assert_eq!(x.value, "a&b");
+  let y = iter.next().unwrap();
+  assert_eq!(y.key, "y");
+  assert!(matches!(y.value, std::borrow::Cow::Borrowed("plain")));
+  assert_eq!(iter.next(), None);
+
+  // entities that can't be decoded are kept, not a panic
+  let iter = Tais::new_decoding(r#"a="R&D" b="&nbsp;&lt;" c="&""#);
+  let values: Vec<_> = iter.map(|a| a.value).collect();
+  assert_eq!(values, ["R&D", "&nbsp;<", "&"]);
+}
+
+#[test]
+fn test_is_done() {
+  let mut iter = Tais::new(r#" a="1" b='2' "#);
+  assert!(!iter.is_done());
+  assert!(iter.next().is_some());
+  assert!(!iter.is_done());
+  assert!(iter.next().is_some());
+  assert!(iter.is_done());
+  assert_eq!(iter.next(), None);
+
+  assert!(Tais::new("  ").is_done());
+}
+
+#[test]
+fn test_tag_attribute_display() {
+  let attr = TagAttribute { key: "a", value: "b" };
+  assert_eq!(format!("{}", attr), r#"a="b""#);
+
+  let attr = TagAttribute { key: "quote", value: r#"say "hi""# };
+  assert_eq!(format!("{}", attr), r#"quote='say "hi"'"#);
+
+  // both quotes: the `"` is escaped so the output is still well formed
+  let attr = TagAttribute { key: "k", value: r#"a"b'c"# };
+  let text = attr.to_string();
+  assert_eq!(text, r#"k="a&quot;b'c""#);
+  let back = TagAttributeIterator::new(&text).next().unwrap();
+  let mut buf = [0_u8; 16];
+  let len = back.decode_value_into(&mut buf).unwrap();
+  assert_eq!(&buf[..len], br#"a"b'c"#);
+
+  // round trip
+  let attrs = r#"name="GRAPHIC_POINTS" value='0x0000' text='"&amp;"'"#;
+  let out: Vec<String> = Tais::new(attrs).map(|a| a.to_string()).collect();
+  assert_eq!(
+    out.join(" "),
+    r#"name="GRAPHIC_POINTS" value="0x0000" text='"&amp;"'"#
+  );
+}
+
+#[test]
+fn test_lenient_valueless_attributes() {
+  let got: Vec<_> = Tais::new_lenient("checked disabled").collect();
+  assert_eq!(
+    got,
+    [
+      TagAttribute { key: "checked", value: "" },
+      TagAttribute { key: "disabled", value: "" },
+    ]
+  );
+  let got: Vec<_> =
+    Tais::new_lenient(" a='1' flag\n b = \"2\" last ").collect();
+  assert_eq!(
+    got,
+    [
+      TagAttribute { key: "a", value: "1" },
+      TagAttribute { key: "flag", value: "" },
+      TagAttribute { key: "b", value: "2" },
+      TagAttribute { key: "last", value: "" },
+    ]
+  );
+  let attrs = "x y='2'";
+  let spans: Vec<_> = Tais::new_lenient(attrs).with_spans().collect();
+  assert_eq!(spans[0].1.key, 0..1);
+  assert_eq!(spans[0].1.value, 1..1);
+
+  // the default is unchanged
+  assert_eq!(Tais::new("checked disabled").next(), None);
+  assert_eq!(
+    Tais::new("checked").try_next().unwrap().unwrap_err().kind,
+    XmlErrorKind::MalformedAttribute
+  );
+  // other problems are still problems
+  assert_eq!(
+    Tais::new_lenient("a='1").try_next().unwrap().unwrap_err().kind,
+    XmlErrorKind::UnterminatedAttributeValue
+  );
+}
+
+#[test]
+fn test_try_next() {
+  let mut iter = Tais::new(r#"a="b"#);
+  let err =
+    XmlError { kind: XmlErrorKind::UnterminatedAttributeValue, offset: 0 };
+  assert_eq!(iter.try_next(), Some(Err(err)));
+  assert_eq!(iter.try_next(), None);
+  assert!(iter.is_done());
+
+  // mismatched quotes are unterminated too
+  let mut iter = Tais::new(r#"  x='1'  a="b' c='d'"#);
+  assert_eq!(iter.try_next(), Some(Ok(TagAttribute { key: "x", value: "1" })));
+  let err =
+    XmlError { kind: XmlErrorKind::UnterminatedAttributeValue, offset: 9 };
+  assert_eq!(iter.try_next(), Some(Err(err)));
+  assert_eq!(iter.try_next(), None);
+
+  let mut iter = Tais::new("a=b");
+  let err = XmlError { kind: XmlErrorKind::MalformedAttribute, offset: 0 };
+  assert_eq!(iter.try_next(), Some(Err(err)));
+
+  let mut iter = Tais::new("a='1' checked");
+  assert!(iter.try_next().unwrap().is_ok());
+  let err = XmlError { kind: XmlErrorKind::MalformedAttribute, offset: 6 };
+  assert_eq!(iter.try_next(), Some(Err(err)));
+
+  // the normal iteration just stops
+  let mut iter = Tais::new(r#"a='1' b="2"#);
+  assert_eq!(iter.next(), Some(TagAttribute { key: "a", value: "1" }));
+  assert_eq!(iter.next(), None);
+}
+
+#[test]
+fn test_enumerate_attributes() {
+  let attrs = r#"name="GRAPHIC_POINTS" value="0x0000""#;
+  let got: Vec<_> = Tais::new(attrs).enumerate_attributes().collect();
+  assert_eq!(
+    got,
+    vec![
+      (0, TagAttribute { key: "name", value: "GRAPHIC_POINTS" }),
+      (1, TagAttribute { key: "value", value: "0x0000" }),
+    ]
+  );
+
+  // a malformed tail doesn't change the earlier indexes
+  let attrs = r#"a="1" b="2" c="3"#;
+  let got: Vec<_> = Tais::new(attrs).enumerate_attributes().collect();
+  assert_eq!(
+    got,
+    vec![
+      (0, TagAttribute { key: "a", value: "1" }),
+      (1, TagAttribute { key: "b", value: "2" }),
+    ]
+  );
+}
+
+#[test]
+fn test_namespace_declarations() {
+  let default = TagAttribute { key: "xmlns", value: "urn:default" };
+  assert!(default.is_namespace_declaration());
+  assert_eq!(default.namespace_prefix(), None);
+
+  let prefixed = TagAttribute { key: "xmlns:x", value: "urn:x" };
+  assert!(prefixed.is_namespace_declaration());
+  assert_eq!(prefixed.namespace_prefix(), Some("x"));
+
+  for &key in ["name", "xmlnsx", "x:xmlns", "xml:lang"].iter() {
+    let attr = TagAttribute { key, value: "v" };
+    assert!(!attr.is_namespace_declaration(), "{}", key);
+    assert_eq!(attr.namespace_prefix(), None, "{}", key);
+  }
+
+  let attrs = r#"xmlns="urn:d" xmlns:a="urn:a" a:b="c""#;
+  let prefixes: Vec<_> = Tais::new(attrs)
+    .filter(TagAttribute::is_namespace_declaration)
+    .map(|a| a.namespace_prefix())
+    .collect();
+  assert_eq!(prefixes, vec![None, Some("a")]);
+}
+
+#[test]
+fn test_with_spans() {
+  let attrs = "\n  name=\"GRAPHIC_POINTS\"  value='0x0000'\n  empty=\"\"  ";
+  let spans: Vec<_> = Tais::new(attrs).with_spans().collect();
+  assert_eq!(spans.len(), 3);
+  for (attr, span) in &spans {
+    assert_eq!(&attrs[span.key.clone()], attr.key);
+    assert_eq!(&attrs[span.value.clone()], attr.value);
+  }
+  assert_eq!(spans[0].1, AttrSpan { key: 3..7, value: 9..23 });
+  assert_eq!(spans[2].1.value, 50..50);
+  assert_eq!(&attrs[spans[1].1.value.end..][..1], "'");
+}
+
+#[test]
+fn test_find_attr_and_rest() {
+  let attrs = r#"  name="GRAPHIC_POINTS" value="0x0000" "#;
+  let (name, rest) = find_attr_and_rest(attrs, "name").unwrap();
+  assert_eq!(name, "GRAPHIC_POINTS");
+  assert_eq!(rest, r#"value="0x0000""#);
+  let (value, rest) = find_attr_and_rest(rest, "value").unwrap();
+  assert_eq!(value, "0x0000");
+  assert_eq!(rest, "");
+
+  // keys are only searched for after the starting point
+  assert_eq!(find_attr_and_rest(rest, "name"), None);
+  let (_, rest) = find_attr_and_rest(attrs, "value").unwrap();
+  assert_eq!(find_attr_and_rest(rest, "name"), None);
+
+  assert_eq!(find_attr_and_rest(r#"a="1" b"#, "b"), None);
+}
+
+#[test]
+fn test_multi_line_attributes() {
+  let attrs = "a\n  =\n  \"1\"\n  b=\"2\"";
+  let got: Vec<_> = Tais::new(attrs).collect();
+  assert_eq!(
+    got,
+    vec![
+      TagAttribute { key: "a", value: "1" },
+      TagAttribute { key: "b", value: "2" }
+    ]
+  );
+
+  let attrs = "\n\tname =\t'GRAPHIC_POINTS'\r\n\tvalue\t= \"0x0000\"\n";
+  let got: Vec<_> = Tais::new(attrs).collect();
+  assert_eq!(
+    got,
+    vec![
+      TagAttribute { key: "name", value: "GRAPHIC_POINTS" },
+      TagAttribute { key: "value", value: "0x0000" },
+    ]
+  );
+
+  // the values themselves are left alone
+  let got: Vec<_> = Tais::new("a=' 1\n'").collect();
+  assert_eq!(got, vec![TagAttribute { key: "a", value: " 1\n" }]);
+
+  let mut iter =
+    ElementIterator::new("<enum\n  name=\"A\"\n  value = \"1\"\n/>");
+  let (_, attrs) = match iter.next() {
+    Some(XmlElement::EmptyTag { name, attrs }) => (name, attrs),
+    other => panic!("{:?}", other),
+  };
+  assert_eq!(Tais::new(attrs).find_by_key("value"), Some("1"));
+}
+
+#[test]
+fn test_find_by_key_last() {
+  let iter = Tais::new(r#"a="1" a="2""#);
+  assert_eq!(iter.find_by_key_last("a"), Some("2"));
+  assert_eq!(iter.find_by_key("a"), Some("1"));
+  assert_eq!(Tais::new(r#"a="1" b="2""#).find_by_key_last("a"), Some("1"));
+  assert_eq!(Tais::new("").find_by_key_last("a"), None);
+}
+
+#[test]
+fn test_typed_getters() {
+  let iter = Tais::new(concat!(
+    r#"t="true" f="false" one="1" zero="0" yes="yes" x="2.25" n="-1e-2" "#,
+    r#"nan="x" u="42" h="0x1F" neg="-1" big="18446744073709551616""#,
+  ));
+  assert_eq!(iter.get_bool("t"), Some(true));
+  assert_eq!(iter.get_bool("f"), Some(false));
+  assert_eq!(iter.get_bool("one"), Some(true));
+  assert_eq!(iter.get_bool("zero"), Some(false));
+  assert_eq!(iter.get_bool("yes"), None);
+  assert_eq!(iter.get_bool("missing"), None);
+
+  assert_eq!(iter.get_f64("x"), Some(2.25));
+  assert_eq!(iter.get_f64("n"), Some(-0.01));
+  assert_eq!(iter.get_f64("u"), Some(42.0));
+  assert_eq!(iter.get_f64("nan"), None);
+  assert_eq!(iter.get_f64("missing"), None);
+
+  assert_eq!(iter.get_u64("u"), Some(42));
+  assert_eq!(iter.get_u64("h"), Some(0x1F));
+  assert_eq!(iter.get_u64("one"), Some(1));
+  assert_eq!(iter.get_u64("neg"), None);
+  assert_eq!(iter.get_u64("x"), None);
+  assert_eq!(iter.get_u64("big"), None);
+  assert_eq!(iter.get_u64("missing"), None);
+  // no sign, even where `u64::from_str_radix` would allow one
+  let iter = Tais::new(r#"plus="+5" hex_plus="0x+f" hex_neg="0x-0""#);
+  assert_eq!(iter.get_u64("plus"), None);
+  assert_eq!(iter.get_u64("hex_plus"), None);
+  assert_eq!(iter.get_u64("hex_neg"), None);
+}
+
+#[test]
+fn test_map_values() {
+  let attrs = r#"name="GRAPHIC_POINTS" value="0x0000""#;
+  let same: Vec<_> = Tais::new(attrs).map_values(|v| v).collect();
+  let plain: Vec<_> = Tais::new(attrs).collect();
+  assert_eq!(same, plain);
+
+  // a lookup that gives back fixed slices
+  let lower = |v: &str| match v {
+    "GRAPHIC_POINTS" => "graphic_points",
+    "GRAPHIC_LINES" => "graphic_lines",
+    _ => "unknown",
+  };
+  let got: Vec<_> = Tais::new(attrs).map_values(lower).collect();
+  assert_eq!(
+    got,
+    [
+      TagAttribute { key: "name", value: "graphic_points" },
+      TagAttribute { key: "value", value: "unknown" },
+    ]
+  );
+}
+
+#[test]
+fn test_empty_and_whitespace_values() {
+  let attrs = r#"a="" b="  ""#;
+  let got: Vec<_> = Tais::new(attrs).collect();
+  assert_eq!(
+    got,
+    [
+      TagAttribute { key: "a", value: "" },
+      TagAttribute { key: "b", value: "  " }
+    ]
+  );
+  let iter = Tais::new(attrs);
+  assert_eq!(iter.find_by_key("a"), Some(""));
+  assert_eq!(iter.find_by_key("b"), Some("  "));
+  assert_eq!(iter.find_by_key("c"), None);
+  assert!(iter.contains_key("a"));
+
+  // single quotes, and whitespace around the `=`
+  let got: Vec<_> = Tais::new("a = '' b='\t\n '").collect();
+  assert_eq!(
+    got,
+    [
+      TagAttribute { key: "a", value: "" },
+      TagAttribute { key: "b", value: "\t\n " }
+    ]
+  );
+
+  // the values round trip through `Display`
+  for attr in Tais::new(attrs) {
+    let text = format!("{}", attr);
+    assert_eq!(TagAttributeIterator::new(&text).next(), Some(attr));
+  }
+
+  // and through a tag
+  let xml = r#"<t a="" b="   "/>"#;
+  let (_, attrs) = match ElementIterator::new(xml).next() {
+    Some(XmlElement::EmptyTag { name, attrs }) => (name, attrs),
+    other => panic!("{:?}", other),
+  };
+  assert_eq!(Tais::new(attrs).find_by_key("a"), Some(""));
+  assert_eq!(Tais::new(attrs).find_by_key("b"), Some("   "));
+}
+
+#[test]
+fn test_contains_key() {
+  let iter = Tais::new(r#"name="GRAPHIC_POINTS" value="0x0000" empty="""#);
+  assert!(iter.contains_key("name"));
+  assert!(iter.contains_key("value"));
+  assert!(iter.contains_key("empty"));
+  assert!(!iter.contains_key("alias"));
+  assert!(!iter.contains_key("0x0000"));
+  assert!(!iter.contains_key(""));
+  // the iterator itself isn't advanced
+  assert_eq!(iter.count(), 3);
+}
+
+#[test]
+fn test_value_in() {
+  let allowed = ["true", "false"];
+  assert!(TagAttribute { key: "optional", value: "true" }.value_in(&allowed));
+  assert!(TagAttribute { key: "optional", value: "false" }.value_in(&allowed));
+  assert!(!TagAttribute { key: "optional", value: "TRUE" }.value_in(&allowed));
+  assert!(!TagAttribute { key: "optional", value: " true" }.value_in(&allowed));
+  assert!(!TagAttribute { key: "optional", value: "" }.value_in(&allowed));
+  assert!(!TagAttribute { key: "optional", value: "true" }.value_in(&[]));
+}
+
+#[test]
+fn test_extract() {
+  let iter = Tais::new(r#"name="GRAPHIC_POINTS" value="0x0000" value="dup""#);
+  let mut out = [None, None];
+  iter.extract(&["name", "value"], &mut out);
+  assert_eq!(out, [Some("GRAPHIC_POINTS"), Some("0x0000")]);
+
+  // old values are cleared
+  let mut out = [Some("stale"), Some("stale")];
+  Tais::new(r#"a="1""#).extract(&["b", "a"], &mut out);
+  assert_eq!(out, [None, Some("1")]);
+
+  let mut out: [Option<&str>; 0] = [];
+  Tais::new(r#"a="1""#).extract(&[], &mut out);
+}
+
+#[test]
+#[should_panic]
+fn test_extract_length_mismatch() {
+  let mut out = [None];
+  Tais::new(r#"a="1""#).extract(&["a", "b"], &mut out);
+}
+
+#[test]
+fn test_empty_iterator() {
+  let mut iter = Tais::empty();
+  assert!(iter.is_done());
+  assert_eq!(iter.next(), None);
+  assert_eq!(iter.try_next(), None);
+  assert_eq!(Tais::default().next(), None);
+}
+
+#[test]
+fn test_value_tokens() {
+  let attr = TagAttribute { key: "class", value: "a  b   c" };
+  assert!(attr.value_tokens().eq(["a", "b", "c"]));
+
+  let attr = TagAttribute { key: "requires", value: "\n  x\ty \r\n" };
+  assert!(attr.value_tokens().eq(["x", "y"]));
+
+  for value in ["", "   ", "\t\n"] {
+    let attr = TagAttribute { key: "class", value };
+    assert_eq!(attr.value_tokens().count(), 0);
+  }
+
+  // the tokens borrow from the source, not the attribute
+  let xml = String::from(r#"required="GL_A GL_B""#);
+  let tokens: Vec<&str> = {
+    let attr = TagAttributeIterator::new(&xml).next().unwrap();
+    attr.value_tokens().collect()
+  };
+  assert_eq!(tokens, ["GL_A", "GL_B"]);
+}
@@ -0,0 +1,108 @@
+use magnesium::*;
+
+/// An owned copy of an element, so events can be checked after the buffer is
+/// reused.
+fn describe(el: XmlElement<'_>) -> String {
+  format!("{:?}", el)
+}
+
+const XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<registry>
+  <!-- We're gonna pretend that there's a whole file here -->
+  <enums group="GraphicPolygons">
+    <enum name="GRAPHIC_POINTS" value="0x0000" comment="1>0" />
+    <enum name="GRAPHIC_LINES" value="0x0001" />
+  </enums>
+  <text>a &lt; b — ünïcödé</text><![CDATA[<raw>]]>
+</registry>
+"#;
+
+#[test]
+fn test_one_byte_at_a_time() {
+  let mut buffer = [0_u8; 64];
+  let mut parser = CoreReadParser::new(&mut buffer);
+  let mut events = Vec::new();
+  for byte in XML.as_bytes().chunks(1) {
+    parser.feed(byte, |el| events.push(describe(el))).unwrap();
+  }
+  parser.finish(|el| events.push(describe(el))).unwrap();
+
+  let expected: Vec<String> = ElementIteratorBuilder::new()
+    .keep_declaration(true)
+    .build(XML)
+    .map(describe)
+    .collect();
+  // the builder trims the input, so the parser's whitespace is extra.
+  assert_eq!(events.first(), expected.first());
+  assert_eq!(events.last(), Some(&describe(XmlElement::Text("\n"))));
+  assert_eq!(&events[..events.len() - 1], &expected[..]);
+}
+
+#[test]
+fn test_chunk_sizes_agree() {
+  let mut all = Vec::new();
+  for size in 1..=XML.len() {
+    let mut buffer = [0_u8; 64];
+    let mut parser = CoreReadParser::new(&mut buffer);
+    let mut events = Vec::new();
+    for chunk in XML.as_bytes().chunks(size) {
+      parser.feed(chunk, |el| events.push(describe(el))).unwrap();
+    }
+    parser.finish(|el| events.push(describe(el))).unwrap();
+    all.push(events);
+  }
+  assert!(all.windows(2).all(|w| w[0] == w[1]));
+  // the `>` in the attribute value doesn't end the tag for any chunk size
+  let points = XmlElement::EmptyTag {
+    name: "enum",
+    attrs: r#"name="GRAPHIC_POINTS" value="0x0000" comment="1>0""#,
+  };
+  assert!(all[0].contains(&describe(points)));
+}
+
+#[test]
+fn test_buffer_overflow() {
+  let mut buffer = [0_u8; 8];
+  let mut parser = CoreReadParser::new(&mut buffer);
+  let mut count = 0;
+  parser.feed(b"<a>", |_| count += 1).unwrap();
+  let err = parser.feed(br#"<b long="attribute"/>"#, |_| count += 1);
+  assert_eq!(
+    err,
+    Err(XmlError { kind: XmlErrorKind::BufferOverflow, offset: 3 })
+  );
+  assert_eq!(count, 1);
+}
+
+#[test]
+fn test_core_read_errors() {
+  let mut buffer = [0_u8; 16];
+  let mut parser = CoreReadParser::new(&mut buffer);
+  parser.feed(b"<a><b", |_| ()).unwrap();
+  assert_eq!(
+    parser.finish(|_| ()),
+    Err(XmlError { kind: XmlErrorKind::UnexpectedEnd, offset: 3 })
+  );
+
+  let mut buffer = [0_u8; 16];
+  let mut parser = CoreReadParser::new(&mut buffer);
+  assert_eq!(
+    parser.feed(b"<a>x\xFFy</a>", |_| ()),
+    Err(XmlError { kind: XmlErrorKind::InvalidUtf8, offset: 4 })
+  );
+
+  let mut buffer = [0_u8; 16];
+  let mut parser = CoreReadParser::new(&mut buffer);
+  parser.feed("<a>é".as_bytes().split_last().unwrap().1, |_| ()).unwrap();
+  assert_eq!(
+    parser.finish(|_| ()),
+    Err(XmlError { kind: XmlErrorKind::InvalidUtf8, offset: 3 })
+  );
+
+  let mut buffer = [0_u8; 16];
+  let mut parser = CoreReadParser::new(&mut buffer);
+  assert_eq!(
+    parser.feed(b"<a></>", |_| ()),
+    Err(XmlError { kind: XmlErrorKind::EmptyTagName, offset: 3 })
+  );
+}
@@ -0,0 +1,100 @@
+use magnesium::*;
+
+#[test]
+fn test_peek_and_bump() {
+  let xml = "<a x='1'>text<b/></a>";
+  let mut cursor = XmlCursor::new(xml);
+  let a = XmlElement::StartTag { name: "a", attrs: "x='1'" };
+  assert_eq!(cursor.peek(), Some(a));
+  assert_eq!(cursor.peek(), Some(a));
+  assert_eq!(cursor.remaining(), xml);
+  assert_eq!(cursor.bump(), Some(a));
+  assert_eq!(cursor.remaining(), "text<b/></a>");
+  assert_eq!(cursor.peek(), Some(XmlElement::Text("text")));
+  assert_eq!(cursor.bump(), Some(XmlElement::Text("text")));
+  assert_eq!(
+    cursor.bump(),
+    Some(XmlElement::EmptyTag { name: "b", attrs: "" })
+  );
+  assert!(!cursor.at_end());
+  assert_eq!(cursor.peek(), Some(XmlElement::EndTag { name: "a" }));
+  assert_eq!(cursor.bump(), Some(XmlElement::EndTag { name: "a" }));
+  assert!(cursor.at_end());
+  assert_eq!(cursor.peek(), None);
+  assert_eq!(cursor.bump(), None);
+  assert_eq!(cursor.remaining(), "");
+}
+
+#[test]
+fn test_at_end() {
+  assert!(XmlCursor::new("").at_end());
+  assert!(XmlCursor::default().at_end());
+  // input that can't be parsed is the end
+  let cursor = XmlCursor::new("<a");
+  assert!(cursor.at_end());
+  assert_eq!(cursor.remaining(), "<a");
+}
+
+/// A tiny recursive descent parser that counts the items in each list.
+fn parse_list(cursor: &mut XmlCursor<'_>) -> Option<Vec<usize>> {
+  let name = match cursor.bump()? {
+    XmlElement::StartTag { name, .. } => name,
+    XmlElement::EmptyTag { .. } => return Some(vec![0]),
+    _ => return None,
+  };
+  let mut counts = vec![0];
+  loop {
+    match cursor.peek()? {
+      XmlElement::EndTag { name: end } if end == name => {
+        cursor.bump();
+        return Some(counts);
+      }
+      XmlElement::StartTag { name: "list", .. }
+      | XmlElement::EmptyTag { name: "list", .. } => {
+        counts.extend(parse_list(cursor)?);
+      }
+      _ => {
+        counts[0] += 1;
+        cursor.bump();
+      }
+    }
+  }
+}
+
+#[test]
+fn test_recursive_descent() {
+  let xml = "<list><i/><list><i/><i/></list><i/><list/></list>";
+  let mut cursor = XmlCursor::new(xml);
+  assert_eq!(parse_list(&mut cursor), Some(vec![2, 2, 0]));
+  assert!(cursor.at_end());
+}
+
+#[test]
+fn test_current_attributes() {
+  let xml =
+    r#"<enums group="Polygon" namespace="GL">text<enum value="1"/></enums>"#;
+  let mut cursor = XmlCursor::new(xml);
+  assert_eq!(cursor.current_attributes().next(), None);
+
+  cursor.bump();
+  let attrs: Vec<_> = cursor.current_attributes().collect();
+  assert_eq!(
+    attrs,
+    [
+      TagAttribute { key: "group", value: "Polygon" },
+      TagAttribute { key: "namespace", value: "GL" },
+    ]
+  );
+  // peeking doesn't change the current element
+  let _ = cursor.peek();
+  assert_eq!(cursor.current_attributes().find_by_key("namespace"), Some("GL"));
+
+  cursor.bump();
+  assert_eq!(cursor.current_attributes().next(), None);
+  cursor.bump();
+  assert_eq!(cursor.current_attributes().find_by_key("value"), Some("1"));
+  cursor.bump();
+  assert_eq!(cursor.current_attributes().next(), None);
+  assert_eq!(cursor.bump(), None);
+  assert_eq!(cursor.current_attributes().next(), None);
+}
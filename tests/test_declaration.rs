@@ -0,0 +1,75 @@
+use magnesium::*;
+
+#[test]
+fn test_full_declaration() {
+  let xml = r#"
+    <?xml version="1.0" encoding="UTF-8" standalone="no"?>
+    <registry/>
+  "#;
+  assert_eq!(
+    parse_declaration(xml),
+    Some(XmlDeclaration {
+      version: "1.0",
+      encoding: Some("UTF-8"),
+      standalone: Some(false)
+    })
+  );
+
+  let xml = r#"<?xml version='1.1' standalone='yes' ?>"#;
+  assert_eq!(
+    parse_declaration(xml),
+    Some(XmlDeclaration {
+      version: "1.1",
+      encoding: None,
+      standalone: Some(true)
+    })
+  );
+}
+
+#[test]
+fn test_bare_declaration() {
+  let xml = r#"<?xml version="1.0"?>"#;
+  assert_eq!(
+    parse_declaration(xml),
+    Some(XmlDeclaration { version: "1.0", encoding: None, standalone: None })
+  );
+}
+
+#[test]
+fn test_bad_declarations() {
+  assert_eq!(parse_declaration(""), None);
+  assert_eq!(parse_declaration("<registry/>"), None);
+  assert_eq!(parse_declaration(r#"<?xml version="1.0""#), None);
+  assert_eq!(parse_declaration(r#"<?xml encoding="UTF-8"?>"#), None);
+  assert_eq!(parse_declaration(r#"<?xml-stylesheet version="1.0"?>"#), None);
+  assert_eq!(
+    parse_declaration(r#"<?xml version="1.0" standalone="maybe"?>"#),
+    None
+  );
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn test_parse_internal_entities() {
+  let doctype = r#"<!DOCTYPE page [
+  <!ENTITY copy "&#169;">
+  <!ENTITY % shared "ignored">
+  <!ENTITY logo SYSTEM "logo.png">
+  <!ENTITY broken "no end>
+  <!ENTITY reg '&#xAE;'>
+  <!ENTITY motto "A > B" >
+]>"#;
+  let entities = parse_internal_entities(doctype);
+  assert_eq!(
+    entities,
+    [("copy", "&#169;"), ("reg", "&#xAE;"), ("motto", "A > B")]
+  );
+
+  // the values still need their character references decoded
+  let (_, copy) = entities.iter().find(|(name, _)| *name == "copy").unwrap();
+  assert_eq!(decode_xml_lossy(copy), "©");
+
+  assert!(parse_internal_entities("<!DOCTYPE page>").is_empty());
+  assert!(parse_internal_entities("<!ENTITY").is_empty());
+  assert!(parse_internal_entities("<!ENTITYcopy \"x\">").is_empty());
+}
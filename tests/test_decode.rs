@@ -0,0 +1,148 @@
+use core::fmt::{self, Write};
+use magnesium::*;
+
+/// A fixed capacity sink, like a `no_std` string type would be.
+struct SmallBuf {
+  buf: [u8; 16],
+  len: usize,
+}
+impl SmallBuf {
+  fn new() -> Self {
+    Self { buf: [0; 16], len: 0 }
+  }
+  fn as_str(&self) -> &str {
+    core::str::from_utf8(&self.buf[..self.len]).unwrap()
+  }
+}
+impl Write for SmallBuf {
+  fn write_str(&mut self, s: &str) -> fmt::Result {
+    let end = self.len + s.len();
+    self
+      .buf
+      .get_mut(self.len..end)
+      .ok_or(fmt::Error)?
+      .copy_from_slice(s.as_bytes());
+    self.len = end;
+    Ok(())
+  }
+}
+
+#[test]
+fn test_write_decoded() {
+  let mut out = SmallBuf::new();
+  assert_eq!(write_decoded(&mut out, "a&lt;b&#x3E;c&#38;"), Ok(()));
+  assert_eq!(out.as_str(), "a<b>c&");
+
+  let mut out = SmallBuf::new();
+  assert_eq!(write_decoded(&mut out, "&quot;&apos;é"), Ok(()));
+  assert_eq!(out.as_str(), "\"'é");
+
+  let mut out = SmallBuf::new();
+  assert_eq!(write_decoded(&mut out, ""), Ok(()));
+  assert_eq!(out.as_str(), "");
+}
+
+#[test]
+fn test_write_decoded_errors() {
+  let mut out = SmallBuf::new();
+  let err = DecodeError { kind: DecodeErrorKind::UnknownEntity, offset: 2 };
+  assert_eq!(write_decoded(&mut out, "ab&nbsp;"), Err(err));
+  assert_eq!(out.as_str(), "ab");
+
+  let mut out = SmallBuf::new();
+  let err =
+    DecodeError { kind: DecodeErrorKind::UnterminatedEntity, offset: 1 };
+  assert_eq!(write_decoded(&mut out, "a&amp"), Err(err));
+
+  let mut out = SmallBuf::new();
+  let err = DecodeError { kind: DecodeErrorKind::InvalidCharRef, offset: 0 };
+  assert_eq!(write_decoded(&mut out, "&#xD800;"), Err(err));
+
+  let mut out = SmallBuf::new();
+  let err = DecodeError { kind: DecodeErrorKind::Write, offset: 15 };
+  assert_eq!(write_decoded(&mut out, "0123456789&amp;0123456789"), Err(err));
+  assert_eq!(out.as_str(), "0123456789&");
+}
+
+#[test]
+fn test_decode_error_display() {
+  let err = DecodeError { kind: DecodeErrorKind::UnknownEntity, offset: 7 };
+  let text = format!("{}", err);
+  assert!(text.contains(&format!("{}", DecodeErrorKind::UnknownEntity)));
+  assert!(text.contains('7'));
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn test_decode_xml_lossy() {
+  assert_eq!(decode_xml_lossy("&unknown; &amp; more"), "&unknown; & more");
+  assert_eq!(decode_xml_lossy("a&#x42;&#67;"), "aBC");
+  assert_eq!(decode_xml_lossy("&#xD800; &#;"), "&#xD800; &#;");
+  assert_eq!(decode_xml_lossy("fish & chips &lt;3"), "fish & chips <3");
+  assert_eq!(decode_xml_lossy("trailing &amp"), "trailing &amp");
+  assert_eq!(decode_xml_lossy("&&&lt;"), "&&<");
+  assert_eq!(decode_xml_lossy(""), "");
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn test_decode_bare_ampersand() {
+  assert_eq!(decode_xml_lossy("R&D"), "R&D");
+  assert_eq!(decode_xml_lossy("R&D; &lt;"), "R&D; <");
+  assert_eq!(decode_xml_lossy("R&D and 1 &lt; 2;"), "R&D and 1 < 2;");
+  assert_eq!(decode_xml_lossy("a & b;"), "a & b;");
+  let policy = DecodePolicy::ReplaceUnknownWith('?');
+  // a bare `&` doesn't swallow the text up to the next `;`.
+  assert_eq!(decode_xml_with_policy("R&D and &lt;", policy), "R?D and <");
+  assert_eq!(decode_xml_with_policy("&nbsp;&lt;", policy), "?<");
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn test_decode_policy() {
+  let text = "a &amp; &unknown; b";
+  assert_eq!(
+    decode_xml_with_policy(text, DecodePolicy::PassthroughUnknown),
+    "a & &unknown; b"
+  );
+  assert_eq!(
+    decode_xml_with_policy(text, DecodePolicy::ReplaceUnknownWith('\u{FFFD}')),
+    "a & \u{FFFD} b"
+  );
+  assert_eq!(
+    decode_xml_with_policy("x &amp", DecodePolicy::ReplaceUnknownWith('?')),
+    "x ?amp"
+  );
+  assert_eq!(
+    decode_xml_with_policy("&#x41;&quot;", DecodePolicy::Strict),
+    "A\""
+  );
+  assert_eq!(revert_xml_encoding("&#x41;&apos;"), "A'");
+
+  let result = std::panic::catch_unwind(|| {
+    decode_xml_with_policy(text, DecodePolicy::Strict)
+  });
+  assert!(result.is_err());
+  let result = std::panic::catch_unwind(|| revert_xml_encoding(text));
+  assert!(result.is_err());
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn test_predefined_entities_table() {
+  let names: Vec<_> = XML_PREDEFINED_ENTITIES.iter().map(|(n, _)| *n).collect();
+  assert_eq!(names, ["lt", "gt", "amp", "quot", "apos"]);
+  let texts: String = XML_PREDEFINED_ENTITIES.iter().map(|(_, t)| *t).collect();
+  assert_eq!(texts, "<>&\"'");
+
+  for (name, text) in XML_PREDEFINED_ENTITIES.iter() {
+    let entity = format!("&{};", name);
+    let mut out = String::new();
+    write_decoded(&mut out, &entity).unwrap();
+    assert_eq!(out, *text);
+    assert_eq!(decode_xml_lossy(&entity), *text);
+    assert_eq!(revert_xml_encoding(&entity), *text);
+    let policy = DecodePolicy::ReplaceUnknownWith('?');
+    assert_eq!(decode_xml_with_policy(&entity, policy), *text);
+  }
+}
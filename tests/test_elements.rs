@@ -64,7 +64,7 @@ fn test_parsing() {
     iter.next(),
     Some(XmlElement::EmptyTag {
       name: "enum",
-      attrs: r#"name="GRAPHIC_POINTS" value="0x0000" "#
+      attrs: r#"name="GRAPHIC_POINTS" value="0x0000""#
     })
   );
   assert_eq!(iter.next(), Some(XmlElement::Text("\n        ")));
@@ -72,7 +72,7 @@ fn test_parsing() {
     iter.next(),
     Some(XmlElement::EmptyTag {
       name: "enum",
-      attrs: r#"name="GRAPHIC_LINES" value="0x0001" "#
+      attrs: r#"name="GRAPHIC_LINES" value="0x0001""#
     })
   );
   assert_eq!(iter.next(), Some(XmlElement::Text("\n      ")));
@@ -139,14 +139,14 @@ fn test_whitespace_skipping_parsing() {
     iter.next(),
     Some(XmlElement::EmptyTag {
       name: "enum",
-      attrs: r#"name="GRAPHIC_POINTS" value="0x0000" "#
+      attrs: r#"name="GRAPHIC_POINTS" value="0x0000""#
     })
   );
   assert_eq!(
     iter.next(),
     Some(XmlElement::EmptyTag {
       name: "enum",
-      attrs: r#"name="GRAPHIC_LINES" value="0x0001" "#
+      attrs: r#"name="GRAPHIC_LINES" value="0x0001""#
     })
   );
   assert_eq!(iter.next(), Some(XmlElement::EndTag { name: "enums" }));
@@ -207,14 +207,14 @@ fn test_skipping_comments_parsing() {
     iter.next(),
     Some(XmlElement::EmptyTag {
       name: "enum",
-      attrs: r#"name="GRAPHIC_POINTS" value="0x0000" "#
+      attrs: r#"name="GRAPHIC_POINTS" value="0x0000""#
     })
   );
   assert_eq!(
     iter.next(),
     Some(XmlElement::EmptyTag {
       name: "enum",
-      attrs: r#"name="GRAPHIC_LINES" value="0x0001" "#
+      attrs: r#"name="GRAPHIC_LINES" value="0x0001""#
     })
   );
   assert_eq!(iter.next(), Some(XmlElement::EndTag { name: "enums" }));
@@ -233,3 +233,1772 @@ fn test_empty_tag_no_attrs() {
     Some(XmlElement::EmptyTag { name: "apientry", attrs: "" })
   );
 }
+
+#[test]
+#[cfg(feature = "alloc")]
+fn test_parse_all() {
+  let xml = r#"
+    <?xml version="1.0" encoding="UTF-8"?>
+    <registry>
+      <!-- We're gonna pretend that there's a whole file here -->
+      <types>
+        <type>typedef unsigned int <name>GraphicsEnum</name>;</type>
+      </types>
+      <enums group="GraphicPolygons">
+        <enum name="GRAPHIC_POINTS" value="0x0000" />
+        <enum name="GRAPHIC_LINES" value="0x0001" />
+      </enums>
+    </registry>
+  "#;
+
+  let elements = parse_all(xml);
+  assert_eq!(elements.len(), 25);
+  assert_eq!(elements, ElementIterator::new(xml).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_strict_text_before_root() {
+  let xml = "stray <registry><enum/></registry>";
+
+  // normal iteration lets it slide
+  let mut iter = ElementIterator::new(xml);
+  assert_eq!(iter.next(), Some(XmlElement::Text("stray ")));
+
+  let mut iter = ElementIterator::new_strict(xml);
+  assert_eq!(
+    iter.next(),
+    Some(XmlElement::Error(XmlError {
+      kind: XmlErrorKind::TextOutsideRoot,
+      offset: 0
+    }))
+  );
+  assert_eq!(iter.next(), None);
+}
+
+#[test]
+fn test_strict_text_after_root() {
+  let xml = "<!-- hi -->\n<registry>\n  <enum/>\n</registry>\nstray";
+
+  let mut iter = ElementIterator::new_strict(xml);
+  assert_eq!(iter.next(), Some(XmlElement::Comment(" hi ")));
+  assert_eq!(iter.next(), Some(XmlElement::Text("\n")));
+  assert_eq!(
+    iter.next(),
+    Some(XmlElement::StartTag { name: "registry", attrs: "" })
+  );
+  assert_eq!(iter.next(), Some(XmlElement::Text("\n  ")));
+  assert_eq!(
+    iter.next(),
+    Some(XmlElement::EmptyTag { name: "enum", attrs: "" })
+  );
+  assert_eq!(iter.next(), Some(XmlElement::Text("\n")));
+  assert_eq!(iter.next(), Some(XmlElement::EndTag { name: "registry" }));
+  assert_eq!(
+    iter.next(),
+    Some(XmlElement::Error(XmlError {
+      kind: XmlErrorKind::TextOutsideRoot,
+      offset: xml.find("\nstray").unwrap()
+    }))
+  );
+  assert_eq!(iter.next(), None);
+}
+
+#[test]
+fn test_cdata() {
+  let xml = "<a>x<![CDATA[<b>&amp;</b>]]></a>";
+
+  let mut iter = ElementIterator::new(xml);
+  assert_eq!(iter.next(), Some(XmlElement::StartTag { name: "a", attrs: "" }));
+  assert_eq!(iter.next(), Some(XmlElement::Text("x")));
+  assert_eq!(iter.next(), Some(XmlElement::CData("<b>&amp;</b>")));
+  assert_eq!(iter.next(), Some(XmlElement::EndTag { name: "a" }));
+  assert_eq!(iter.next(), None);
+
+  let mut iter = ElementIterator::new_strict(" <![CDATA[x]]><a/>");
+  assert_eq!(
+    iter.next(),
+    Some(XmlElement::Error(XmlError {
+      kind: XmlErrorKind::TextOutsideRoot,
+      offset: 1
+    }))
+  );
+}
+
+#[test]
+fn test_structural_key() {
+  let elements = [
+    XmlElement::StartTag { name: "a", attrs: "b=\"c\"" },
+    XmlElement::EndTag { name: "a" },
+    XmlElement::EmptyTag { name: "a", attrs: "b=\"c\"" },
+    XmlElement::Text("a"),
+    XmlElement::Comment("a"),
+    XmlElement::CData("a"),
+    XmlElement::Error(XmlError {
+      kind: XmlErrorKind::TextOutsideRoot,
+      offset: 0,
+    }),
+  ];
+  for (i, x) in elements.iter().enumerate() {
+    for y in &elements[i + 1..] {
+      assert_ne!(x.structural_key().0, y.structural_key().0);
+    }
+  }
+
+  assert_eq!(elements[0].structural_key(), (0, "a", "b=\"c\""));
+  assert_eq!(elements[1].structural_key(), (1, "a", ""));
+  assert_eq!(elements[3].structural_key(), (3, "a", ""));
+}
+
+#[test]
+fn test_children_of() {
+  let xml = r#"
+    <registry>
+      <enums group="GraphicPolygons">
+        <!-- a comment -->
+        <enum name="GRAPHIC_POINTS" value="0x0000" />
+        <enum name="GRAPHIC_LINES" value="0x0001" />
+      </enums>
+      <after/>
+    </registry>
+  "#;
+
+  let mut iter = ElementIterator::new(xml);
+  assert_eq!(
+    iter.find(|el| matches!(el, XmlElement::StartTag { name: "enums", .. })),
+    Some(XmlElement::StartTag {
+      name: "enums",
+      attrs: r#"group="GraphicPolygons""#
+    })
+  );
+  let mut children = iter.children_of("enums");
+  assert_eq!(
+    children.next(),
+    Some(XmlElement::EmptyTag {
+      name: "enum",
+      attrs: r#"name="GRAPHIC_POINTS" value="0x0000""#
+    })
+  );
+  assert_eq!(
+    children.next(),
+    Some(XmlElement::EmptyTag {
+      name: "enum",
+      attrs: r#"name="GRAPHIC_LINES" value="0x0001""#
+    })
+  );
+  assert_eq!(children.next(), None);
+  assert_eq!(children.next(), None);
+
+  // the parent iterator picks up after the close
+  let mut iter = iter.filter_map(skip_empty_text_elements);
+  assert_eq!(
+    iter.next(),
+    Some(XmlElement::EmptyTag { name: "after", attrs: "" })
+  );
+}
+
+#[test]
+fn test_children_of_skips_grandchildren() {
+  let xml = "<a><b><c/><d></d></b><e/></a>";
+
+  let mut iter = ElementIterator::new(xml);
+  let _a = iter.next();
+  let children: Vec<_> = iter.children_of("a").collect();
+  assert_eq!(
+    children,
+    vec![
+      XmlElement::StartTag { name: "b", attrs: "" },
+      XmlElement::EmptyTag { name: "e", attrs: "" },
+    ]
+  );
+  assert_eq!(iter.next(), None);
+}
+
+#[test]
+fn test_empty_tag_names() {
+  for xml in ["<a><></a>", "<a></></a>", "<a></ ></a>", "<a><  /></a>"].iter() {
+    let mut iter = ElementIterator::new(xml);
+    assert_eq!(
+      iter.next(),
+      Some(XmlElement::StartTag { name: "a", attrs: "" })
+    );
+    assert_eq!(iter.next(), None, "{}", xml);
+
+    let mut iter = ElementIterator::new_strict(xml);
+    assert_eq!(
+      iter.next(),
+      Some(XmlElement::StartTag { name: "a", attrs: "" })
+    );
+    assert_eq!(
+      iter.next(),
+      Some(XmlElement::Error(XmlError {
+        kind: XmlErrorKind::EmptyTagName,
+        offset: 3
+      })),
+      "{}",
+      xml
+    );
+    assert_eq!(iter.next(), None);
+  }
+}
+
+#[test]
+fn test_builder() {
+  let xml = r#"
+    <?xml version="1.0" encoding="UTF-8"?>
+    <registry>
+      <!-- We're gonna pretend that there's a whole file here -->
+      <types>
+        <type>typedef unsigned int <name>GraphicsEnum</name>;</type>
+      </types>
+    </registry>
+  "#;
+
+  // default settings match `new`
+  assert!(ElementIteratorBuilder::new()
+    .build(xml)
+    .eq(ElementIterator::new(xml)));
+
+  // matches the filter_map helpers
+  let iter = ElementIteratorBuilder::new()
+    .skip_whitespace(true)
+    .skip_comments(true)
+    .build(xml);
+  assert!(iter.eq(
+    ElementIterator::new(xml)
+      .filter_map(skip_empty_text_elements)
+      .filter_map(skip_comments)
+  ));
+
+  // declaration and comment kept, whitespace skipped
+  let mut iter = ElementIteratorBuilder::new()
+    .skip_whitespace(true)
+    .keep_declaration(true)
+    .strict(true)
+    .build(xml);
+  assert_eq!(
+    iter.next(),
+    Some(XmlElement::ProcessingInstruction {
+      target: "xml",
+      data: r#"version="1.0" encoding="UTF-8""#
+    })
+  );
+  assert_eq!(
+    iter.next(),
+    Some(XmlElement::StartTag { name: "registry", attrs: "" })
+  );
+  assert_eq!(
+    iter.next(),
+    Some(XmlElement::Comment(
+      " We're gonna pretend that there's a whole file here "
+    ))
+  );
+  assert_eq!(
+    iter.next(),
+    Some(XmlElement::StartTag { name: "types", attrs: "" })
+  );
+}
+
+#[test]
+fn test_builder_strict() {
+  let mut iter = ElementIteratorBuilder::new()
+    .skip_comments(true)
+    .strict(true)
+    .build("<!-- x --><a/>text");
+  assert_eq!(iter.next(), Some(XmlElement::EmptyTag { name: "a", attrs: "" }));
+  assert_eq!(
+    iter.next(),
+    Some(XmlElement::Error(XmlError {
+      kind: XmlErrorKind::TextOutsideRoot,
+      offset: 14
+    }))
+  );
+  assert_eq!(iter.next(), None);
+}
+
+#[test]
+fn test_processing_instruction() {
+  let xml = r#"<a><?xml-stylesheet href="style.css" ?><?empty?></a>"#;
+
+  let mut iter = ElementIterator::new(xml);
+  assert_eq!(iter.next(), Some(XmlElement::StartTag { name: "a", attrs: "" }));
+  assert_eq!(
+    iter.next(),
+    Some(XmlElement::ProcessingInstruction {
+      target: "xml-stylesheet",
+      data: r#"href="style.css""#
+    })
+  );
+  assert_eq!(
+    iter.next(),
+    Some(XmlElement::ProcessingInstruction { target: "empty", data: "" })
+  );
+  assert_eq!(iter.next(), Some(XmlElement::EndTag { name: "a" }));
+  assert_eq!(iter.next(), None);
+}
+
+#[test]
+fn test_inner_xml() {
+  let mut iter = ElementIterator::new("<wrap><a/><b/></wrap>");
+  assert_eq!(
+    iter.next(),
+    Some(XmlElement::StartTag { name: "wrap", attrs: "" })
+  );
+  assert_eq!(iter.inner_xml("wrap"), Some("<a/><b/>"));
+  assert_eq!(iter.next(), None);
+
+  // nesting, including the same name, is balanced
+  let xml = "<r><wrap>\n  <wrap><x/></wrap>\n  text\n</wrap><after/></r>";
+  let mut iter = ElementIterator::new(xml);
+  let _r = iter.next();
+  let _wrap = iter.next();
+  assert_eq!(iter.inner_xml("wrap"), Some("\n  <wrap><x/></wrap>\n  text\n"));
+  assert_eq!(
+    iter.next(),
+    Some(XmlElement::EmptyTag { name: "after", attrs: "" })
+  );
+
+  // empty body
+  let mut iter = ElementIterator::new("<wrap></wrap>");
+  let _wrap = iter.next();
+  assert_eq!(iter.inner_xml("wrap"), Some(""));
+
+  // never closed
+  let mut iter = ElementIterator::new("<wrap><a/>");
+  let _wrap = iter.next();
+  assert_eq!(iter.inner_xml("wrap"), None);
+  assert_eq!(iter.next(), None);
+}
+
+#[test]
+fn test_inner_xml_trimmed() {
+  let mut iter = ElementIterator::new("<wrap>\n  <a/>\n</wrap>");
+  let _wrap = iter.next();
+  assert_eq!(iter.inner_xml_trimmed("wrap"), Some("<a/>"));
+  assert_eq!(iter.next(), None);
+
+  // only the outside is trimmed
+  let xml = "<r><wrap>\n  <x/>\n  text \n</wrap><after/></r>";
+  let mut iter = ElementIterator::new(xml);
+  let _r = iter.next();
+  let _wrap = iter.next();
+  assert_eq!(iter.inner_xml_trimmed("wrap"), Some("<x/>\n  text"));
+  assert_eq!(
+    iter.next(),
+    Some(XmlElement::EmptyTag { name: "after", attrs: "" })
+  );
+
+  let mut iter = ElementIterator::new("<wrap> </wrap>");
+  let _wrap = iter.next();
+  assert_eq!(iter.inner_xml_trimmed("wrap"), Some(""));
+
+  let mut iter = ElementIterator::new("<wrap> <a/>");
+  let _wrap = iter.next();
+  assert_eq!(iter.inner_xml_trimmed("wrap"), None);
+}
+
+#[test]
+fn test_skip_element() {
+  let xml = r#"<registry>
+  <extensions>
+    <extension name="a"><require><extensions/></require></extension>
+    <extensions>nested</extensions>
+  </extensions>
+  <enums/>
+</registry>"#;
+  let mut iter = ElementIteratorBuilder::new().skip_whitespace(true).build(xml);
+  let _registry = iter.next();
+  assert_eq!(
+    iter.next(),
+    Some(XmlElement::StartTag { name: "extensions", attrs: "" })
+  );
+  assert!(iter.skip_element("extensions"));
+  assert_eq!(
+    iter.next(),
+    Some(XmlElement::EmptyTag { name: "enums", attrs: "" })
+  );
+  assert_eq!(iter.next(), Some(XmlElement::EndTag { name: "registry" }));
+
+  // never closed
+  let mut iter = ElementIterator::new("<a><b></b>");
+  let _a = iter.next();
+  assert!(!iter.skip_element("a"));
+  assert_eq!(iter.next(), None);
+}
+
+#[test]
+fn test_raw_inner_text() {
+  let xml =
+    "<r><a>x &amp; y</a><b></b><c>1<i/>2</c><d><!--c--></d><e>z</e></r>";
+  let mut iter = ElementIterator::new(xml);
+  let _r = iter.next();
+  let _a = iter.next();
+  assert_eq!(iter.raw_inner_text("a"), Some("x &amp; y"));
+  let _b = iter.next();
+  assert_eq!(iter.raw_inner_text("b"), Some(""));
+  // not contiguous, but the iterator still moves past the tag
+  let _c = iter.next();
+  assert_eq!(iter.raw_inner_text("c"), None);
+  let _d = iter.next();
+  assert_eq!(iter.raw_inner_text("d"), None);
+  let _e = iter.next();
+  assert_eq!(iter.raw_inner_text("e"), Some("z"));
+  assert_eq!(iter.next(), Some(XmlElement::EndTag { name: "r" }));
+
+  let mut iter = ElementIterator::new("<a>never closed");
+  let _a = iter.next();
+  assert_eq!(iter.raw_inner_text("a"), None);
+}
+
+#[test]
+fn test_last_complete_boundary() {
+  assert_eq!(last_complete_boundary(""), 0);
+  assert_eq!(last_complete_boundary("<a></a>"), 7);
+
+  // trailing partial tag
+  let xml = "<a>\n  <b/>\n  <c name=\"x";
+  assert_eq!(&xml[..last_complete_boundary(xml)], "<a>\n  <b/>\n  ");
+
+  // a `>` within a value that's still open doesn't close the tag
+  assert_eq!(last_complete_boundary("<r><a x=\"1>2"), 3);
+  assert_eq!(last_complete_boundary("<r><a x='1>2'/>"), 15);
+
+  // trailing partial comment
+  let xml = "<a><b></b><!-- not done -";
+  assert_eq!(&xml[..last_complete_boundary(xml)], "<a><b></b>");
+
+  // trailing text might continue
+  let xml = "<a><b></b>some te";
+  assert_eq!(&xml[..last_complete_boundary(xml)], "<a><b></b>");
+
+  // the declaration counts once closed
+  let xml = "<?xml version=\"1.0\"?>\n<a>";
+  assert_eq!(last_complete_boundary(xml), xml.len());
+  assert_eq!(last_complete_boundary("<?xml version="), 0);
+}
+
+#[test]
+fn test_visit_elements() {
+  use core::ops::ControlFlow;
+  let xml = r#"
+    <registry>
+      <enums group="GraphicPolygons">
+        <enum name="GRAPHIC_POINTS" value="0x0000" />
+        <enum name="GRAPHIC_LINES" value="0x0001" />
+      </enums>
+    </registry>
+  "#;
+
+  let mut count = 0;
+  visit_elements(xml, |_| {
+    count += 1;
+    ControlFlow::Continue(())
+  });
+  assert_eq!(count, ElementIterator::new(xml).count());
+
+  let mut seen = Vec::new();
+  visit_elements(xml, |el| {
+    seen.push(el);
+    match el {
+      XmlElement::StartTag { .. } => ControlFlow::Break(()),
+      _ => ControlFlow::Continue(()),
+    }
+  });
+  assert_eq!(seen, [XmlElement::StartTag { name: "registry", attrs: "" }]);
+}
+
+#[test]
+fn test_eq_str() {
+  assert!(XmlElement::StartTag { name: "enums", attrs: "a=\"b\"" } == "enums");
+  assert!(XmlElement::EndTag { name: "enums" } == "enums");
+  assert!(XmlElement::EmptyTag { name: "enums", attrs: "" } == "enums");
+
+  assert!(XmlElement::StartTag { name: "enum", attrs: "" } != "enums");
+  assert!(XmlElement::EndTag { name: "enum" } != "enums");
+  assert!(XmlElement::EmptyTag { name: "enum", attrs: "" } != "enums");
+
+  assert!(XmlElement::Text("enums") != "enums");
+  assert!(XmlElement::CData("enums") != "enums");
+  assert!(XmlElement::Comment("enums") != "enums");
+  assert!(
+    XmlElement::ProcessingInstruction { target: "enums", data: "" } != "enums"
+  );
+
+  let count = ElementIterator::new("<enums><enum/><enum/></enums>")
+    .filter(|el| *el == "enum")
+    .count();
+  assert_eq!(count, 2);
+}
+
+#[test]
+fn test_prologue_whitespace() {
+  let xml = "<?xml version=\"1.0\"?>\n  \t\n<registry/>";
+  let iter = ElementIterator::new(xml);
+  assert_eq!(iter.prologue_whitespace(), "\n  \t\n");
+  // the declaration, prologue, and body put the input back together
+  let decl_end = xml.find("?>").unwrap() + 2;
+  assert_eq!(
+    [&xml[..decl_end], iter.prologue_whitespace(), "<registry/>"].concat(),
+    xml
+  );
+
+  assert_eq!(ElementIterator::new("  <registry/>").prologue_whitespace(), "  ");
+  assert_eq!(ElementIterator::new("<registry/>").prologue_whitespace(), "");
+  assert_eq!(ElementIterator::new("").prologue_whitespace(), "");
+  assert_eq!(
+    ElementIteratorBuilder::new()
+      .keep_declaration(true)
+      .build("\n<?xml version=\"1.0\"?><registry/>")
+      .prologue_whitespace(),
+    "\n"
+  );
+}
+
+#[test]
+fn test_non_ascii_content() {
+  let xml = "<größe maß=\"→\">ü<ß/></größe>";
+
+  let mut iter = ElementIterator::new(xml);
+  assert_eq!(
+    iter.next(),
+    Some(XmlElement::StartTag { name: "größe", attrs: "maß=\"→\"" })
+  );
+  assert_eq!(iter.next(), Some(XmlElement::Text("ü")));
+  assert_eq!(iter.next(), Some(XmlElement::EmptyTag { name: "ß", attrs: "" }));
+  assert_eq!(iter.next(), Some(XmlElement::EndTag { name: "größe" }));
+  assert_eq!(iter.next(), None);
+
+  let mut attrs = TagAttributeIterator::new("maß=\"→\" ü='ß'");
+  assert_eq!(attrs.next(), Some(TagAttribute { key: "maß", value: "→" }));
+  assert_eq!(attrs.next(), Some(TagAttribute { key: "ü", value: "ß" }));
+  assert_eq!(attrs.next(), None);
+}
+
+#[test]
+fn test_map_tags() {
+  let xml = "<type>typedef int <name>GLint</name>;</type>";
+
+  assert!(ElementIterator::new(xml)
+    .map_tags(|n| n)
+    .eq(ElementIterator::new(xml)));
+
+  let renames = [("type", "typedef"), ("name", "ident")];
+  let mut iter = ElementIterator::new(xml).map_tags(|n| {
+    renames.iter().find(|(old, _)| *old == n).map(|(_, new)| *new).unwrap_or(n)
+  });
+  assert_eq!(
+    iter.next(),
+    Some(XmlElement::StartTag { name: "typedef", attrs: "" })
+  );
+  assert_eq!(iter.next(), Some(XmlElement::Text("typedef int ")));
+  assert_eq!(
+    iter.next(),
+    Some(XmlElement::StartTag { name: "ident", attrs: "" })
+  );
+  assert_eq!(iter.next(), Some(XmlElement::Text("GLint")));
+  assert_eq!(iter.next(), Some(XmlElement::EndTag { name: "ident" }));
+  assert_eq!(iter.next(), Some(XmlElement::Text(";")));
+  assert_eq!(iter.next(), Some(XmlElement::EndTag { name: "typedef" }));
+  assert_eq!(iter.next(), None);
+}
+
+#[test]
+fn test_is_done() {
+  let mut iter = ElementIterator::new("<a>text</a>\n");
+  for _ in 0..3 {
+    assert!(!iter.is_done());
+    assert!(iter.next().is_some());
+  }
+  assert!(iter.is_done());
+  assert_eq!(iter.next(), None);
+  assert!(iter.is_done());
+
+  assert!(ElementIterator::new("").is_done());
+
+  // an input problem also finishes the iterator
+  let mut iter = ElementIterator::new("<a><");
+  assert!(iter.next().is_some());
+  assert!(!iter.is_done());
+  assert_eq!(iter.next(), None);
+  assert!(iter.is_done());
+}
+
+#[test]
+fn test_slash_inside_attribute_values() {
+  let mut iter = ElementIterator::new(r#"<a path="x/"/>"#);
+  assert_eq!(
+    iter.next(),
+    Some(XmlElement::EmptyTag { name: "a", attrs: r#"path="x/""# })
+  );
+  assert_eq!(iter.next(), None);
+
+  let mut iter = ElementIterator::new(r#"<a path="/"/>"#);
+  assert_eq!(
+    iter.next(),
+    Some(XmlElement::EmptyTag { name: "a", attrs: r#"path="/""# })
+  );
+  assert_eq!(iter.next(), None);
+
+  let mut iter = ElementIterator::new(r#"<a path="/">x</a>"#);
+  assert_eq!(
+    iter.next(),
+    Some(XmlElement::StartTag { name: "a", attrs: r#"path="/""# })
+  );
+  assert_eq!(iter.next(), Some(XmlElement::Text("x")));
+  assert_eq!(iter.next(), Some(XmlElement::EndTag { name: "a" }));
+}
+
+#[test]
+fn test_close_angle_inside_attribute_values() {
+  let mut iter = ElementIterator::new(r#"<a expr="1 > 0" b='/>'/>"#);
+  assert_eq!(
+    iter.next(),
+    Some(XmlElement::EmptyTag { name: "a", attrs: r#"expr="1 > 0" b='/>'"# })
+  );
+  assert_eq!(iter.next(), None);
+}
+
+#[test]
+fn test_named() {
+  let xml = r#"
+    <?xml version="1.0" encoding="UTF-8"?>
+    <registry>
+      <!-- <enum name="COMMENTED_OUT"/> -->
+      <enums group="GraphicPolygons">
+        <enum name="GRAPHIC_POINTS" value="0x0000" />
+        <enum name="GRAPHIC_LINES" value="0x0001" />
+      </enums>
+    </registry>
+  "#;
+  let enums: Vec<_> = ElementIterator::new(xml).named("enum").collect();
+  assert_eq!(
+    enums,
+    vec![
+      XmlElement::EmptyTag {
+        name: "enum",
+        attrs: r#"name="GRAPHIC_POINTS" value="0x0000""#
+      },
+      XmlElement::EmptyTag {
+        name: "enum",
+        attrs: r#"name="GRAPHIC_LINES" value="0x0001""#
+      },
+    ]
+  );
+
+  assert_eq!(ElementIterator::new(xml).named("nope").next(), None);
+}
+
+#[test]
+fn test_variant_predicates() {
+  let all = [
+    XmlElement::StartTag { name: "a", attrs: "" },
+    XmlElement::EndTag { name: "a" },
+    XmlElement::EmptyTag { name: "a", attrs: "" },
+    XmlElement::Text("a"),
+    XmlElement::CData("a"),
+    XmlElement::Comment("a"),
+    XmlElement::ProcessingInstruction { target: "a", data: "" },
+  ];
+  let expected = [
+    // start, end, self_closing, text, comment
+    [true, false, false, false, false],
+    [false, true, false, false, false],
+    [false, false, true, false, false],
+    [false, false, false, true, false],
+    [false, false, false, false, false],
+    [false, false, false, false, true],
+    [false, false, false, false, false],
+  ];
+  for (el, expected) in all.iter().zip(expected.iter()) {
+    let got = [
+      el.is_start(),
+      el.is_end(),
+      el.is_self_closing(),
+      el.is_text(),
+      el.is_comment(),
+    ];
+    assert_eq!(&got, expected, "{:?}", el);
+  }
+
+  let mut iter = ElementIterator::new_strict("text");
+  let error = iter.next().unwrap();
+  assert!(matches!(error, XmlElement::Error(_)));
+  assert!(!error.is_start());
+  assert!(!error.is_end());
+  assert!(!error.is_self_closing());
+  assert!(!error.is_text());
+  assert!(!error.is_comment());
+}
+
+#[test]
+fn test_comment_terminators() {
+  let xml = "<root><!-- a --!><!-- b --></root>";
+
+  // the default only accepts `-->`
+  let mut iter = ElementIterator::new(xml);
+  assert_eq!(
+    iter.next(),
+    Some(XmlElement::StartTag { name: "root", attrs: "" })
+  );
+  assert_eq!(iter.next(), Some(XmlElement::Comment(" a --!><!-- b ")));
+
+  let mut iter =
+    ElementIterator::new_with_comment_terminators(xml, &["-->", "--!>"]);
+  assert_eq!(
+    iter.next(),
+    Some(XmlElement::StartTag { name: "root", attrs: "" })
+  );
+  assert_eq!(iter.next(), Some(XmlElement::Comment(" a ")));
+  assert_eq!(iter.next(), Some(XmlElement::Comment(" b ")));
+  assert_eq!(iter.next(), Some(XmlElement::EndTag { name: "root" }));
+  assert_eq!(iter.next(), None);
+
+  let mut iter = ElementIterator::new_with_comment_terminators(xml, &[]);
+  assert_eq!(
+    iter.next(),
+    Some(XmlElement::StartTag { name: "root", attrs: "" })
+  );
+  assert_eq!(iter.next(), None);
+
+  // the comment opener's dashes can't also close the comment
+  let mut iter = ElementIterator::new("<!--->");
+  assert_eq!(iter.next(), None);
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn test_zip_with_end_tags() {
+  let xml = r#"<?xml version="1.0"?>
+<registry>
+  <types><type>int</type></types>
+  <enums group="G">
+    <enum name="A"/>
+    <enums><x>1</x></enums>
+  </enums>
+</registry>"#;
+  let pairs = ElementIterator::new(xml).zip_with_end_tags();
+  let names: Vec<&str> =
+    pairs.iter().map(|(el, _)| el.unwrap_start_tag().0).collect();
+  assert_eq!(names, ["registry", "types", "type", "enums", "enums", "x"]);
+  for (el, end) in &pairs {
+    let name = el.unwrap_start_tag().0;
+    let close = format!("</{}>", name);
+    assert_eq!(&xml[*end..*end + close.len()], close);
+  }
+  // the outer `enums` gets the outer close, not the inner one
+  let outer = pairs[3].1;
+  let inner = pairs[4].1;
+  assert!(inner < outer);
+  assert_eq!(&xml[outer..], "</enums>\n</registry>");
+
+  // unclosed tags are left out, and stray closes are skipped
+  let pairs = ElementIterator::new("<a><b></c></a>").zip_with_end_tags();
+  assert_eq!(pairs, vec![(XmlElement::StartTag { name: "a", attrs: "" }, 10)]);
+}
+
+#[test]
+fn test_text_boundary() {
+  let mut iter = ElementIterator::new_with_text_boundary("<t>a{b</t>", '{');
+  assert_eq!(iter.next(), Some(XmlElement::StartTag { name: "t", attrs: "" }));
+  assert_eq!(iter.next(), Some(XmlElement::Text("a")));
+  assert_eq!(iter.next(), Some(XmlElement::Text("{b")));
+  assert_eq!(iter.next(), Some(XmlElement::EndTag { name: "t" }));
+  assert_eq!(iter.next(), None);
+
+  // repeated and non-ASCII boundaries
+  let mut iter = ElementIterator::new_with_text_boundary("<t>§§x</t>", '§');
+  let _t = iter.next();
+  assert_eq!(iter.next(), Some(XmlElement::Text("§")));
+  assert_eq!(iter.next(), Some(XmlElement::Text("§x")));
+  assert_eq!(iter.next(), Some(XmlElement::EndTag { name: "t" }));
+
+  // the default doesn't split
+  let mut iter = ElementIterator::new("<t>a{b</t>");
+  let _t = iter.next();
+  assert_eq!(iter.next(), Some(XmlElement::Text("a{b")));
+}
+
+#[test]
+fn test_max_depth() {
+  let xml = r#"
+    <?xml version="1.0" encoding="UTF-8"?>
+    <registry>
+      <types>
+        <type>typedef unsigned int</type>
+      </types>
+      <comment>flat</comment>
+    </registry>
+  "#;
+  assert_eq!(max_depth(xml), 3);
+
+  assert_eq!(max_depth("<a><b><c/></b></a>"), 3);
+  assert_eq!(max_depth("text only"), 0);
+
+  // malformed input gives the depth seen before the problem
+  assert_eq!(max_depth("<a><b><c></c><"), 3);
+  assert_eq!(max_depth("<a><b><></b></a>"), 2);
+}
+
+#[test]
+fn test_has_root_element() {
+  let comments_only = r#"
+    <?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+    <!-- We're gonna pretend that there's a whole file here -->
+    <!-- but there isn't -->
+  "#;
+  assert!(!has_root_element(comments_only));
+  assert!(!has_root_element(""));
+  assert!(!has_root_element("  \n "));
+  assert!(!has_root_element("<?pi data?>"));
+
+  let normal = r#"
+    <?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+    <!-- a comment first -->
+    <registry>
+      <types/>
+    </registry>
+  "#;
+  assert!(has_root_element(normal));
+  assert!(has_root_element("<root/>"));
+}
+
+#[test]
+fn test_end_tag_with_attributes() {
+  let xml = r#"<foo></foo bar="1">"#;
+  let mut iter = ElementIterator::new(xml);
+  assert_eq!(
+    iter.next(),
+    Some(XmlElement::StartTag { name: "foo", attrs: "" })
+  );
+  assert_eq!(iter.next(), Some(XmlElement::EndTag { name: "foo" }));
+  assert_eq!(iter.next(), None);
+
+  let mut iter = ElementIterator::new("<foo></foo\n>");
+  let _foo = iter.next();
+  assert_eq!(iter.next(), Some(XmlElement::EndTag { name: "foo" }));
+
+  let mut iter = ElementIterator::new_strict(xml);
+  let _foo = iter.next();
+  assert_eq!(
+    iter.next(),
+    Some(XmlElement::Error(XmlError {
+      kind: XmlErrorKind::EndTagAttributes,
+      offset: 5
+    }))
+  );
+  assert_eq!(iter.next(), None);
+
+  // trailing whitespace alone is fine, even in strict mode
+  let mut iter = ElementIterator::new_strict("<foo></foo >");
+  let _foo = iter.next();
+  assert_eq!(iter.next(), Some(XmlElement::EndTag { name: "foo" }));
+}
+
+#[test]
+fn test_bytes_consumed() {
+  let xml = r#"
+    <?xml version="1.0" encoding="UTF-8"?>
+    <registry>
+      <!-- comment -->
+      <enum name="GRAPHIC_POINTS" value="0x0000" />
+    </registry>
+  "#;
+  let mut iter = ElementIterator::new(xml);
+  let mut last = iter.bytes_consumed();
+  // the declaration and leading whitespace are skipped right away
+  assert_eq!(&xml[last..last + 10], "<registry>");
+  while iter.next().is_some() {
+    let now = iter.bytes_consumed();
+    assert!(now > last);
+    last = now;
+  }
+  assert_eq!(iter.bytes_consumed(), xml.len());
+
+  // also after the input stops early
+  let mut iter = ElementIterator::new("<a><");
+  iter.by_ref().for_each(drop);
+  assert_eq!(iter.bytes_consumed(), 4);
+
+  assert_eq!(ElementIterator::new("").bytes_consumed(), 0);
+}
+
+#[test]
+fn test_strip_cdata() {
+  assert_eq!(strip_cdata("<![CDATA[<raw> & stuff]]>"), Some("<raw> & stuff"));
+  assert_eq!(strip_cdata("<![CDATA[]]>"), Some(""));
+  assert_eq!(strip_cdata("plain value"), None);
+  assert_eq!(strip_cdata("<![CDATA[unclosed"), None);
+  assert_eq!(strip_cdata(" <![CDATA[x]]>"), None);
+  // the opener and closer can't overlap
+  assert_eq!(strip_cdata("<![CDATA]]>"), None);
+
+  let attrs = r#"code="<![CDATA[a<b]]>""#;
+  let value = TagAttributeIterator::new(attrs).find_by_key("code").unwrap();
+  assert_eq!(strip_cdata(value), Some("a<b"));
+}
+
+#[test]
+fn test_from_str_slice() {
+  let xml = r#"
+    <?xml version="1.0"?>
+    <registry><!-- c --><enum name="A"/>text</registry>
+  "#;
+  let from: ElementIterator = xml.into();
+  assert_eq!(
+    from.collect::<Vec<_>>(),
+    ElementIterator::new(xml).collect::<Vec<_>>()
+  );
+  assert_eq!(ElementIterator::from(xml).prologue_whitespace(), "\n    ");
+}
+
+#[test]
+fn test_whitespace_around_tag_names() {
+  let mut iter = ElementIterator::new("< registry ></ registry >");
+  assert_eq!(
+    iter.next(),
+    Some(XmlElement::StartTag { name: "registry", attrs: "" })
+  );
+  assert_eq!(iter.next(), Some(XmlElement::EndTag { name: "registry" }));
+  assert_eq!(iter.next(), None);
+
+  let mut iter =
+    ElementIterator::new("<registry ><\tenum\nname='A'/></registry>");
+  assert_eq!(
+    iter.next(),
+    Some(XmlElement::StartTag { name: "registry", attrs: "" })
+  );
+  assert_eq!(
+    iter.next(),
+    Some(XmlElement::EmptyTag { name: "enum", attrs: "name='A'" })
+  );
+  assert_eq!(iter.next(), Some(XmlElement::EndTag { name: "registry" }));
+
+  // trailing whitespace is fine even when strict
+  let mut iter = ElementIterator::new_strict("<registry ></registry>");
+  assert_eq!(
+    iter.next(),
+    Some(XmlElement::StartTag { name: "registry", attrs: "" })
+  );
+
+  let mut iter = ElementIterator::new_strict("< registry >");
+  assert_eq!(
+    iter.next(),
+    Some(XmlElement::Error(XmlError {
+      kind: XmlErrorKind::WhitespaceBeforeTagName,
+      offset: 0
+    }))
+  );
+  assert_eq!(iter.next(), None);
+}
+
+#[test]
+fn test_peek_name() {
+  let xml = "<registry><!-- c --><enum name='A'/>text</registry>";
+  let mut iter = ElementIterator::new(xml);
+  assert_eq!(iter.peek_name(), Some("registry"));
+  assert_eq!(iter.peek_name(), Some("registry"));
+  assert_eq!(
+    iter.next(),
+    Some(XmlElement::StartTag { name: "registry", attrs: "" })
+  );
+  assert_eq!(iter.peek_name(), None);
+  assert_eq!(iter.next(), Some(XmlElement::Comment(" c ")));
+  assert_eq!(iter.peek_name(), Some("enum"));
+  assert_eq!(
+    iter.next(),
+    Some(XmlElement::EmptyTag { name: "enum", attrs: "name='A'" })
+  );
+  assert_eq!(iter.peek_name(), None);
+  assert_eq!(iter.next(), Some(XmlElement::Text("text")));
+  assert_eq!(iter.peek_name(), Some("registry"));
+  assert_eq!(iter.next(), Some(XmlElement::EndTag { name: "registry" }));
+  assert_eq!(iter.peek_name(), None);
+  assert_eq!(iter.next(), None);
+
+  // skipped elements are skipped when peeking too
+  let iter =
+    ElementIteratorBuilder::new().skip_comments(true).build("<!-- c --><a/>");
+  assert_eq!(iter.peek_name(), Some("a"));
+}
+
+#[test]
+fn test_split_top_level_roots() {
+  let roots: Vec<_> = split_top_level_roots("<a/><b></b>").collect();
+  assert_eq!(roots, ["<a/>", "<b></b>"]);
+
+  let xml = r#"<?xml version="1.0"?>
+    <first x="1"><inner><first/></inner></first>
+    <!-- between -->
+    <second>text &amp; more</second>
+  "#;
+  let roots: Vec<_> = split_top_level_roots(xml).collect();
+  assert_eq!(
+    roots,
+    [
+      r#"<first x="1"><inner><first/></inner></first>"#,
+      "<second>text &amp; more</second>",
+    ]
+  );
+
+  // each root can be parsed on its own
+  for root in split_top_level_roots(xml) {
+    let mut iter = ElementIterator::new_strict(root);
+    assert!(iter.all(|el| !matches!(el, XmlElement::Error(_))));
+  }
+
+  // an unclosed root ends the iteration
+  let roots: Vec<_> = split_top_level_roots("<a/><b><c></c>").collect();
+  assert_eq!(roots, ["<a/>"]);
+
+  assert_eq!(split_top_level_roots("").next(), None);
+}
+
+#[test]
+fn test_accessors_without_matching() {
+  let xml =
+    r#"<registry><enum name="A"/>text<![CDATA[x]]><!-- c --></registry>"#;
+  let mut names = Vec::new();
+  let mut texts = Vec::new();
+  let mut others = 0;
+  for el in ElementIterator::new(xml) {
+    if let Some(name) = el.name() {
+      names.push(name);
+    } else if let Some(text) = el.as_text() {
+      texts.push(text);
+    } else {
+      // any kind of element added later ends up here
+      others += 1;
+    }
+  }
+  assert_eq!(names, ["registry", "enum", "registry"]);
+  assert_eq!(texts, ["text"]);
+  assert_eq!(others, 2);
+
+  let pi = XmlElement::ProcessingInstruction { target: "pi", data: "" };
+  assert_eq!(pi.name(), None);
+  assert_eq!(pi.as_text(), None);
+  assert_eq!(XmlElement::CData("x").as_text(), None);
+}
+
+#[test]
+fn test_keep_comments_where() {
+  let xml = r#"
+    <!-- license: zlib OR MIT -->
+    <registry>
+      <!-- We're gonna pretend that there's a whole file here -->
+      <types/>
+      <!-- the license for this part is also zlib -->
+    </registry>
+  "#;
+  let comments: Vec<_> = ElementIterator::new(xml)
+    .filter_map(keep_comments_where(|c| c.contains("license")))
+    .filter_map(|el| match el {
+      XmlElement::Comment(c) => Some(c),
+      _ => None,
+    })
+    .collect();
+  assert_eq!(
+    comments,
+    [" license: zlib OR MIT ", " the license for this part is also zlib "]
+  );
+
+  let filter = keep_comments_where(|_| false);
+  assert_eq!(filter(XmlElement::Comment("license")), None);
+  assert_eq!(
+    filter(XmlElement::Text("license")),
+    Some(XmlElement::Text("license"))
+  );
+  assert_eq!(
+    filter(XmlElement::EndTag { name: "a" }),
+    Some(XmlElement::EndTag { name: "a" })
+  );
+}
+
+#[test]
+fn test_indexed() {
+  let xml = r#"<?xml version="1.0"?>
+<registry><!-- c --><enum name="A"/>some text</registry>trailing text"#;
+  let items: Vec<_> = ElementIterator::new(xml).indexed().collect();
+  for (start, el) in &items {
+    match el {
+      XmlElement::StartTag { name, .. } | XmlElement::EmptyTag { name, .. } => {
+        assert_eq!(&xml[*start..*start + 1 + name.len()], format!("<{}", name));
+      }
+      XmlElement::EndTag { name } => {
+        assert_eq!(
+          &xml[*start..*start + 2 + name.len()],
+          format!("</{}", name)
+        );
+      }
+      XmlElement::Text(t) => {
+        assert_eq!(&xml[*start..*start + t.len()], *t);
+        let end = *start + t.len();
+        assert!(end == xml.len() || xml[end..].starts_with('<'));
+      }
+      XmlElement::Comment(_) => assert!(xml[*start..].starts_with("<!--")),
+      other => panic!("{:?}", other),
+    }
+  }
+  let (start, last) = items.last().unwrap();
+  assert_eq!(*last, XmlElement::Text("trailing text"));
+  assert_eq!(start + last.as_text().unwrap().len(), xml.len());
+
+  // offsets still line up when elements are skipped
+  let iter = ElementIteratorBuilder::new().skip_comments(true).build(xml);
+  let items: Vec<_> = iter.indexed().map(|(start, _)| start).collect();
+  assert_eq!(items[1], xml.find("<enum").unwrap());
+}
+
+#[test]
+fn test_empty_tag_slash_spacing() {
+  let attrs_of = |xml| match ElementIterator::new(xml).next() {
+    Some(XmlElement::EmptyTag { name: "enum", attrs }) => attrs,
+    other => panic!("{:?}", other),
+  };
+  let forms = [
+    r#"<enum name="X" />"#,
+    r#"<enum name="X"/>"#,
+    "<enum name=\"X\"\n/>",
+    r#"<enum name="X"    />"#,
+  ];
+  let expected = vec![TagAttribute { key: "name", value: "X" }];
+  for &xml in forms.iter() {
+    let attrs: Vec<_> = TagAttributeIterator::new(attrs_of(xml)).collect();
+    assert_eq!(attrs, expected, "{:?}", xml);
+  }
+  assert_eq!(attrs_of(r#"<enum name="X"/>"#), r#"name="X""#);
+
+  // and without any attributes
+  for &xml in ["<enum/>", "<enum />", "<enum\t/>"].iter() {
+    assert_eq!(TagAttributeIterator::new(attrs_of(xml)).next(), None);
+  }
+}
+
+#[test]
+fn test_element_attr() {
+  let xml =
+    r#"<enums group="G"><enum name="GRAPHIC_POINTS" value="0x0000" /></enums>"#;
+  let mut iter = ElementIterator::new(xml);
+  let enums = iter.next().unwrap();
+  assert_eq!(enums.attr("group"), Some("G"));
+  let el = iter.next().unwrap();
+  assert_eq!(el.attr("name"), Some("GRAPHIC_POINTS"));
+  assert_eq!(el.attr("value"), Some("0x0000"));
+  assert_eq!(el.attr("group"), None);
+  assert_eq!(iter.next().unwrap().attr("group"), None);
+  assert_eq!(XmlElement::Text(r#"name="x""#).attr("name"), None);
+}
+
+#[test]
+fn test_root_children() {
+  let xml = r#"
+    <?xml version="1.0" encoding="UTF-8"?>
+    <registry>
+      <!-- We're gonna pretend that there's a whole file here -->
+      <types>
+        <type>typedef unsigned int <name>GraphicsEnum</name>;</type>
+      </types>
+      <enums group="GraphicPolygons">
+        <enum name="GRAPHIC_POINTS" value="0x0000" />
+      </enums>
+    </registry>
+  "#;
+  let children: Vec<_> = root_children(xml).collect();
+  assert_eq!(children.len(), 2);
+  assert!(children[0].starts_with("<types>"));
+  assert!(children[0].ends_with("</types>"));
+  assert!(children[0].contains("<name>GraphicsEnum</name>"));
+  assert!(children[1].starts_with(r#"<enums group="GraphicPolygons">"#));
+  assert!(children[1].ends_with("</enums>"));
+
+  // each child parses on its own
+  let mut iter = ElementIterator::new(children[1]);
+  assert_eq!(
+    iter.next(),
+    Some(XmlElement::StartTag {
+      name: "enums",
+      attrs: r#"group="GraphicPolygons""#
+    })
+  );
+
+  assert_eq!(root_children("<root/>").next(), None);
+  assert_eq!(root_children("").next(), None);
+  let children: Vec<_> = root_children("<r>text<a/></r><after/>").collect();
+  assert_eq!(children, ["<a/>"]);
+}
+
+#[test]
+fn test_empty_iterator() {
+  let mut iter = ElementIterator::empty();
+  assert!(iter.is_done());
+  assert_eq!(iter.next(), None);
+  assert_eq!(iter.bytes_consumed(), 0);
+  assert_eq!(ElementIterator::default().next(), None);
+}
+
+#[test]
+fn test_comment_before_declaration() {
+  let xml = r#"<!-- x --><?xml version="1.0"?><r/>"#;
+  let elements: Vec<_> = ElementIterator::new(xml).collect();
+  assert_eq!(
+    elements,
+    [XmlElement::Comment(" x "), XmlElement::EmptyTag { name: "r", attrs: "" }]
+  );
+
+  let elements: Vec<_> =
+    ElementIteratorBuilder::new().keep_declaration(true).build(xml).collect();
+  assert_eq!(
+    elements[1],
+    XmlElement::ProcessingInstruction {
+      target: "xml",
+      data: r#"version="1.0""#
+    }
+  );
+
+  let mut iter = ElementIterator::new_strict(xml);
+  assert_eq!(iter.next(), Some(XmlElement::Comment(" x ")));
+  let err = XmlError { kind: XmlErrorKind::MisplacedDeclaration, offset: 10 };
+  assert_eq!(iter.next(), Some(XmlElement::Error(err)));
+  assert_eq!(iter.next(), None);
+
+  // a declaration at the start is still fine.
+  let xml = r#" <?xml version="1.0"?><!-- x --><r/>"#;
+  assert!(ElementIterator::new_strict(xml)
+    .all(|el| !matches!(el, XmlElement::Error(_))));
+  let mut iter = ElementIteratorBuilder::new()
+    .keep_declaration(true)
+    .strict(true)
+    .build(xml);
+  assert!(matches!(
+    iter.next(),
+    Some(XmlElement::ProcessingInstruction { target: "xml", .. })
+  ));
+}
+
+#[test]
+fn test_validated() {
+  fn needs_name<'s>(el: &XmlElement<'s>) -> Result<(), &'s str> {
+    match el {
+      XmlElement::EmptyTag { name, .. } if el.attr("name").is_none() => {
+        Err(name)
+      }
+      _ => Ok(()),
+    }
+  }
+  let xml = r#"<r><a name="1"/><b/><c name="3"/></r>"#;
+  let mut iter = ElementIterator::new(xml).validated(needs_name);
+  assert_eq!(
+    iter.next(),
+    Some(Ok(XmlElement::StartTag { name: "r", attrs: "" }))
+  );
+  assert_eq!(
+    iter.next(),
+    Some(Ok(XmlElement::EmptyTag { name: "a", attrs: r#"name="1""# }))
+  );
+  assert_eq!(iter.next(), Some(Err("b")));
+  assert_eq!(iter.next(), None);
+
+  let xml = r#"<r><a name="1"/></r>"#;
+  let all: Result<Vec<_>, _> =
+    ElementIterator::new(xml).validated(needs_name).collect();
+  assert_eq!(all.unwrap().len(), 3);
+}
+
+#[test]
+fn test_display_element() {
+  let cases = [
+    XmlElement::StartTag { name: "a", attrs: "" },
+    XmlElement::StartTag { name: "a", attrs: r#"b="1" c='2'"# },
+    XmlElement::EndTag { name: "a" },
+    XmlElement::EmptyTag { name: "a", attrs: "" },
+    XmlElement::EmptyTag { name: "a", attrs: r#"b="1""# },
+    XmlElement::Text("1 &lt; 2"),
+    XmlElement::CData("<x>"),
+    XmlElement::Comment(" c "),
+    XmlElement::ProcessingInstruction { target: "pi", data: "" },
+    XmlElement::ProcessingInstruction { target: "pi", data: "d" },
+  ];
+  for el in cases.iter() {
+    let text = format!("{}", el);
+    assert_eq!(ElementIterator::new(&text).next().as_ref(), Some(el));
+  }
+}
+
+#[test]
+fn test_write_elements() {
+  let xml = r#"<root a="1">
+  <!-- hi --><b>1 &amp; 2</b><![CDATA[<c>]]><?pi data?>
+  <d x='y'/>
+</root>"#;
+  let mut out = String::new();
+  write_elements(&mut out, ElementIterator::new(xml), false).unwrap();
+  assert_eq!(out, xml);
+
+  let mut out = String::new();
+  write_elements(&mut out, ElementIterator::new(xml), true).unwrap();
+  let expected = r#"<root a="1">
+  <!-- hi -->
+  <b>
+    1 &amp; 2
+  </b>
+  <![CDATA[<c>]]>
+  <?pi data?>
+  <d x='y'/>
+</root>"#;
+  assert_eq!(out, expected);
+
+  let mut out = String::new();
+  write_elements(&mut out, core::iter::empty(), true).unwrap();
+  assert_eq!(out, "");
+}
+
+#[test]
+fn test_coalesce_empty_elements() {
+  let coalesced = |xml, ws| -> Vec<_> {
+    coalesce_empty_elements(ElementIterator::new(xml), ws).collect()
+  };
+  let a = XmlElement::EmptyTag { name: "a", attrs: "" };
+  assert_eq!(coalesced("<a></a>", false), [a]);
+  assert_eq!(coalesced("<a></a>", true), [a]);
+  assert_eq!(
+    coalesced("<a> </a>", false),
+    [
+      XmlElement::StartTag { name: "a", attrs: "" },
+      XmlElement::Text(" "),
+      XmlElement::EndTag { name: "a" },
+    ]
+  );
+  assert_eq!(coalesced("<a> </a>", true), [a]);
+  assert_eq!(coalesced("<a>x</a>", true).len(), 3);
+
+  // nested and mismatched tags
+  assert_eq!(
+    coalesced("<r><a x='1'></a><b></c></b></r>", true),
+    [
+      XmlElement::StartTag { name: "r", attrs: "" },
+      XmlElement::EmptyTag { name: "a", attrs: "x='1'" },
+      XmlElement::StartTag { name: "b", attrs: "" },
+      XmlElement::EndTag { name: "c" },
+      XmlElement::EndTag { name: "b" },
+      XmlElement::EndTag { name: "r" },
+    ]
+  );
+  assert_eq!(
+    coalesced("<r> <a> <b> </b></a></r>", true),
+    [
+      XmlElement::StartTag { name: "r", attrs: "" },
+      XmlElement::Text(" "),
+      XmlElement::StartTag { name: "a", attrs: "" },
+      XmlElement::Text(" "),
+      XmlElement::EmptyTag { name: "b", attrs: "" },
+      XmlElement::EndTag { name: "a" },
+      XmlElement::EndTag { name: "r" },
+    ]
+  );
+}
+
+#[test]
+fn test_byte_offset_of_attrs() {
+  let xml = r#"<r>
+  <enum name="A" value="0x1"/>
+  <group kind="B">text</group>
+</r>"#;
+  for (start, el) in ElementIterator::new(xml).indexed() {
+    let (name, attrs) = match el {
+      XmlElement::StartTag { name, attrs }
+      | XmlElement::EmptyTag { name, attrs }
+        if !attrs.is_empty() =>
+      {
+        (name, attrs)
+      }
+      _ => continue,
+    };
+    let offset = byte_offset_of(attrs, xml).unwrap();
+    assert_eq!(&xml[offset..offset + attrs.len()], attrs);
+    // the attrs come after the `<` and the name of the tag.
+    assert!(offset > start + name.len());
+    if name == "enum" {
+      assert_eq!(offset, 12);
+      let value =
+        TagAttributeIterator::new(attrs).find_by_key("value").unwrap();
+      assert_eq!(byte_offset_of(value, xml), Some(28));
+    }
+  }
+  let copy = String::from(xml);
+  assert_eq!(byte_offset_of(&copy, xml), None);
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn test_decoded_text() {
+  use std::borrow::Cow;
+  assert_eq!(XmlElement::Text("a&amp;b").decoded_text().unwrap(), "a&b");
+  assert_eq!(XmlElement::CData("a&b").decoded_text().unwrap(), "a&b");
+  assert!(matches!(
+    XmlElement::CData("a&amp;b").decoded_text(),
+    Some(Cow::Borrowed("a&amp;b"))
+  ));
+  assert!(matches!(
+    XmlElement::Text("plain").decoded_text(),
+    Some(Cow::Borrowed("plain"))
+  ));
+  assert_eq!(XmlElement::Text("R&D").decoded_text().unwrap(), "R&D");
+  assert_eq!(XmlElement::EndTag { name: "a" }.decoded_text(), None);
+}
+
+#[test]
+fn test_attrs_are_trimmed() {
+  let xml = "<a  x='1'\n><b\ty='2' \n/><c />< d z='3'/></a >";
+  for el in ElementIterator::new(xml) {
+    if let XmlElement::StartTag { attrs, .. }
+    | XmlElement::EmptyTag { attrs, .. } = el
+    {
+      assert_eq!(attrs, attrs.trim(), "{:?}", el);
+    }
+  }
+  let mut iter = ElementIterator::new(xml);
+  assert_eq!(
+    iter.next(),
+    Some(XmlElement::StartTag { name: "a", attrs: "x='1'" })
+  );
+  assert_eq!(
+    iter.next(),
+    Some(XmlElement::EmptyTag { name: "b", attrs: "y='2'" })
+  );
+  assert_eq!(iter.next(), Some(XmlElement::EmptyTag { name: "c", attrs: "" }));
+}
+
+#[test]
+fn test_try_from_str() {
+  use core::convert::TryFrom;
+  let a = XmlElement::EmptyTag { name: "a", attrs: "" };
+  assert_eq!(XmlElement::try_from("<a/>"), Ok(a));
+  assert_eq!(XmlElement::try_from("\n  <a/>\n"), Ok(a));
+  assert_eq!(XmlElement::try_from("text"), Ok(XmlElement::Text("text")));
+  assert_eq!(
+    XmlElement::try_from("<!-- c -->"),
+    Ok(XmlElement::Comment(" c "))
+  );
+
+  let err = |kind, offset| Err(XmlError { kind, offset });
+  assert_eq!(
+    XmlElement::try_from("<a/><b/>"),
+    err(XmlErrorKind::TrailingContent, 4)
+  );
+  assert_eq!(
+    XmlElement::try_from(" <a>text</a>"),
+    err(XmlErrorKind::TrailingContent, 4)
+  );
+  assert_eq!(XmlElement::try_from(""), err(XmlErrorKind::UnexpectedEnd, 0));
+  assert_eq!(XmlElement::try_from("  "), err(XmlErrorKind::UnexpectedEnd, 2));
+  assert_eq!(XmlElement::try_from(" <a"), err(XmlErrorKind::UnexpectedEnd, 1));
+  assert_eq!(XmlElement::try_from("<>"), err(XmlErrorKind::EmptyTagName, 0));
+}
+
+#[test]
+fn test_misplaced_slash() {
+  let first = |xml| ElementIterator::new(xml).next();
+  assert_eq!(
+    first("<a/b>"),
+    Some(XmlElement::StartTag { name: "a", attrs: "/b" })
+  );
+  assert_eq!(
+    first("<a/b/>"),
+    Some(XmlElement::EmptyTag { name: "a", attrs: "/b" })
+  );
+  assert_eq!(
+    first("<a / >"),
+    Some(XmlElement::StartTag { name: "a", attrs: "/" })
+  );
+  // a `/` within an attribute is fine
+  assert_eq!(
+    first("<a href='x/y'/>"),
+    Some(XmlElement::EmptyTag { name: "a", attrs: "href='x/y'" })
+  );
+
+  let strict_first = |xml| ElementIterator::new_strict(xml).next();
+  let err = |offset| {
+    Some(XmlElement::Error(XmlError {
+      kind: XmlErrorKind::MisplacedSlash,
+      offset,
+    }))
+  };
+  assert_eq!(strict_first("<a/b>"), err(0));
+  assert_eq!(strict_first("<a/b/>"), err(0));
+  let mut iter = ElementIterator::new_strict("<r></r/b>");
+  let _r = iter.next();
+  assert_eq!(iter.next(), err(3));
+  assert_eq!(
+    strict_first("<a href='x/y' />"),
+    Some(XmlElement::EmptyTag { name: "a", attrs: "href='x/y'" })
+  );
+}
+
+#[test]
+fn test_strict_cdata_end_in_text() {
+  let xml = "<r>a]]>b</r>";
+  let got: Vec<_> = ElementIterator::new(xml).collect();
+  assert_eq!(got[1], XmlElement::Text("a]]>b"));
+
+  let mut iter = ElementIterator::new_strict(xml);
+  let _r = iter.next();
+  let err = XmlError { kind: XmlErrorKind::CDataEndInText, offset: 3 };
+  assert_eq!(iter.next(), Some(XmlElement::Error(err)));
+  assert_eq!(iter.next(), None);
+
+  // it's fine within CDATA, and a lone `]]` or `>` is fine in text
+  let xml = "<r><![CDATA[a]]]]><![CDATA[>b]]>a]] > b]></r>";
+  assert!(ElementIterator::new_strict(xml)
+    .all(|el| !matches!(el, XmlElement::Error(_))));
+}
+
+#[test]
+fn test_with_byte_ranges() {
+  use core::convert::TryFrom;
+  let xml = r#"<?xml version="1.0"?>
+<registry a="1">
+  <!-- c --><enum name="A" /><![CDATA[<x>]]><?pi d?>text &amp; more
+  <b></b>
+</registry>
+"#;
+  let items: Vec<_> = ElementIterator::new(xml).with_byte_ranges().collect();
+  // the ranges tile the body, from the root tag to the end of the root
+  let body_start = xml.find("<registry").unwrap();
+  let body_end = xml.trim_end().len();
+  assert_eq!(items[0].0.start, body_start);
+  assert_eq!(items.last().unwrap().0.end, body_end);
+  for pair in items.windows(2) {
+    assert_eq!(pair[0].0.end, pair[1].0.start);
+  }
+  // and each one is exactly the element's source
+  for (range, el) in &items {
+    let src = &xml[range.clone()];
+    match el {
+      XmlElement::Text(t) => assert_eq!(t, &src),
+      _ => assert_eq!(XmlElement::try_from(src).as_ref(), Ok(el)),
+    }
+  }
+
+  // skipped elements leave gaps
+  let xml = "<a> <b/> </a>";
+  let iter = ElementIteratorBuilder::new()
+    .skip_whitespace(true)
+    .build(xml)
+    .with_byte_ranges();
+  let ranges: Vec<_> = iter.map(|(r, _)| r).collect();
+  assert_eq!(ranges, [0..3, 4..8, 9..13]);
+
+  // so does a misplaced declaration that a lenient iterator skips
+  let xml = r#"<a><?xml version="1.0"?></a>"#;
+  let ranges: Vec<_> =
+    ElementIterator::new(xml).with_byte_ranges().map(|(r, _)| r).collect();
+  assert_eq!(ranges, [0..3, 24..28]);
+}
+
+#[test]
+fn test_comment_edge_cases() {
+  let all = |xml| ElementIterator::new(xml).collect::<Vec<_>>();
+  let strict = |xml| ElementIterator::new_strict(xml).collect::<Vec<_>>();
+  let malformed = |offset| {
+    XmlElement::Error(XmlError { kind: XmlErrorKind::MalformedComment, offset })
+  };
+
+  assert_eq!(all("<!---->"), [XmlElement::Comment("")]);
+  assert_eq!(strict("<!---->"), [XmlElement::Comment("")]);
+  assert_eq!(all("<!-- -->"), [XmlElement::Comment(" ")]);
+  assert_eq!(strict("<!-- -->"), [XmlElement::Comment(" ")]);
+
+  // never closed
+  assert!(all("<!--->").is_empty());
+  assert_eq!(strict("<!--->"), [malformed(0)]);
+  let r = XmlElement::EmptyTag { name: "r", attrs: "" };
+  assert_eq!(all("<r/><!--->"), [r]);
+  assert_eq!(strict("<r/><!--->"), [r, malformed(4)]);
+
+  // `--` isn't allowed within a comment, or `-` at the end
+  assert_eq!(all("<!-- a -- b -->"), [XmlElement::Comment(" a -- b ")]);
+  assert_eq!(strict("<!-- a -- b -->"), [malformed(0)]);
+  assert_eq!(strict("<!-- a --->"), [malformed(0)]);
+  // but a `-` that's not next to another is fine
+  assert_eq!(strict("<!--->a-->"), [XmlElement::Comment("->a")]);
+}
+
+#[test]
+fn test_find_element() {
+  let xml = "<r>text<!-- c --><a x='1'/><b/></r>";
+  let mut iter = ElementIterator::new(xml);
+  assert_eq!(
+    iter.find_element(XmlElement::is_self_closing),
+    Some(XmlElement::EmptyTag { name: "a", attrs: "x='1'" })
+  );
+  assert_eq!(
+    iter.find_element(|el| matches!(el, XmlElement::EmptyTag { .. })),
+    Some(XmlElement::EmptyTag { name: "b", attrs: "" })
+  );
+  assert_eq!(iter.find_element(XmlElement::is_comment), None);
+  assert_eq!(iter.next(), None);
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn test_with_parent() {
+  let xml = r#"<registry>
+  <enums group="Polygon">
+    <enum name="A"/>
+    <enum name="B"/>
+  </enums>
+  <types><type>text</type></types>
+</registry>"#;
+  let iter = ElementIteratorBuilder::new().skip_whitespace(true).build(xml);
+  let items: Vec<_> = iter.with_parent().collect();
+  for (parent, el) in &items {
+    if el.name() == Some("enum") {
+      assert_eq!(*parent, Some("enums"));
+    }
+  }
+  let parents: Vec<_> = items.iter().map(|(p, _)| *p).collect();
+  assert_eq!(
+    parents,
+    [
+      None,
+      Some("registry"),
+      Some("enums"),
+      Some("enums"),
+      Some("registry"),
+      Some("registry"),
+      Some("types"),
+      Some("type"),
+      Some("types"),
+      Some("registry"),
+      None,
+    ]
+  );
+
+  // an unclosed tag is closed by its parent's end tag, a stray end tag is
+  // skipped
+  let xml = "<a><b><c></b></x><d/></a>";
+  let parents: Vec<_> =
+    ElementIterator::new(xml).with_parent().map(|(p, _)| p).collect();
+  assert_eq!(
+    parents,
+    [None, Some("a"), Some("b"), Some("a"), Some("a"), Some("a"), None]
+  );
+}
+
+#[test]
+fn test_with_parent_bounded() {
+  let xml = "<enums><enum/><enum/></enums>";
+  let parents: Vec<_> = ElementIterator::new(xml)
+    .with_parent_bounded::<4>()
+    .map(|(p, _)| p)
+    .collect();
+  assert_eq!(parents, [None, Some("enums"), Some("enums"), None]);
+
+  // deeper than the stack: unrecorded parents are None, and the depth still
+  // gets back down to the recorded levels.
+  let xml = "<a><b><c><d/></c><e/></b><f/></a>";
+  let mut iter = ElementIterator::new(xml).with_parent_bounded::<2>();
+  let mut parents = Vec::new();
+  let mut max_depth = 0;
+  while let Some((p, _)) = iter.next() {
+    parents.push(p);
+    max_depth = max_depth.max(iter.depth());
+  }
+  assert_eq!(
+    parents,
+    [
+      None,
+      Some("a"),
+      Some("b"),
+      None,
+      Some("b"),
+      Some("b"),
+      Some("a"),
+      Some("a"),
+      None
+    ]
+  );
+  assert_eq!(max_depth, 3);
+  assert_eq!(iter.depth(), 0);
+
+  // a zero size stack never records anything
+  let parents: Vec<_> = ElementIterator::new(xml)
+    .with_parent_bounded::<0>()
+    .map(|(p, _)| p)
+    .collect();
+  assert!(parents.iter().all(Option::is_none));
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn test_with_path() {
+  let xml = r#"<registry>
+  <types>
+    <type name="GLenum"><ptype>unsigned int</ptype></type>
+  </types>
+  <enums><enum name="A"/></enums>
+</registry>"#;
+  let iter = ElementIteratorBuilder::new().skip_whitespace(true).build(xml);
+  let paths: Vec<_> =
+    iter.with_path().map(|(path, el)| (path.to_string(), el.name())).collect();
+  assert_eq!(
+    paths,
+    [
+      ("registry".to_string(), Some("registry")),
+      ("registry/types".to_string(), Some("types")),
+      ("registry/types/type".to_string(), Some("type")),
+      ("registry/types/type/ptype".to_string(), Some("ptype")),
+      ("registry/types/type/ptype".to_string(), None),
+      ("registry/types/type/ptype".to_string(), Some("ptype")),
+      ("registry/types/type".to_string(), Some("type")),
+      ("registry/types".to_string(), Some("types")),
+      ("registry/enums".to_string(), Some("enums")),
+      ("registry/enums/enum".to_string(), Some("enum")),
+      ("registry/enums".to_string(), Some("enums")),
+      ("registry".to_string(), Some("registry")),
+    ]
+  );
+
+  let (path, _) =
+    ElementIterator::new("<!-- top -->").with_path().next().unwrap();
+  assert_eq!(path, ElementPath::default());
+  assert_eq!(path.to_string(), "");
+  assert_eq!(path.last(), None);
+}
+
+#[test]
+fn test_reset() {
+  let xml = "<?xml version=\"1.0\"?>\n<registry><a/>text</registry>";
+  let mut iter = ElementIteratorBuilder::new().strict(true).build(xml);
+  let first: Vec<_> = iter.by_ref().collect();
+  assert_eq!(first.len(), 4);
+  assert_eq!(iter.next(), None);
+
+  iter.reset();
+  assert_eq!(iter.next(), Some(first[0]));
+  let second: Vec<_> = iter.by_ref().collect();
+  assert_eq!(second, first[1..]);
+
+  // partway through, with tags still open
+  iter.reset();
+  iter.next();
+  iter.next();
+  iter.reset();
+  let third: Vec<_> = iter.collect();
+  assert_eq!(third, first);
+}
+
+#[test]
+fn test_gt_in_quoted_value() {
+  let xml = r#"<r><a x="1>2"/></r>"#;
+  let all: Vec<_> = ElementIterator::new(xml).collect();
+  assert_eq!(
+    all,
+    [
+      XmlElement::StartTag { name: "r", attrs: "" },
+      XmlElement::EmptyTag { name: "a", attrs: r#"x="1>2""# },
+      XmlElement::EndTag { name: "r" },
+    ]
+  );
+  // a prefix that ends within the value doesn't cut the tag at the `>`
+  assert_eq!(last_complete_boundary(&xml[..12]), 3);
+}
@@ -0,0 +1,23 @@
+use magnesium::*;
+
+#[test]
+fn test_error_display() {
+  let kinds = [XmlErrorKind::TextOutsideRoot, XmlErrorKind::EmptyTagName];
+  for &kind in kinds.iter() {
+    let err = XmlError { kind, offset: 12 };
+    let kind_text = format!("{}", kind);
+    assert!(!kind_text.is_empty());
+    let err_text = format!("{}", err);
+    assert!(err_text.contains(&kind_text));
+    assert!(err_text.contains("12"));
+    assert!(!format!("{:?}", err).is_empty());
+  }
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn test_error_is_std_error() {
+  let err: Box<dyn std::error::Error> =
+    Box::new(XmlError { kind: XmlErrorKind::EmptyTagName, offset: 0 });
+  assert!(!err.to_string().is_empty());
+}
@@ -0,0 +1,70 @@
+use magnesium::*;
+
+#[test]
+fn test_is_valid_xml_name() {
+  for &name in [
+    "a",
+    "a.b-c",
+    "data-foo.bar",
+    "_x",
+    ":",
+    "xmlns:x",
+    "A1",
+    "é",
+    "日本",
+    "a·b",
+  ]
+  .iter()
+  {
+    assert!(is_valid_xml_name(name), "{:?}", name);
+  }
+  for &name in
+    ["", "1abc", "-a", ".a", "a b", "a=b", "a>", "a/", "·a", "a\u{D7}b"].iter()
+  {
+    assert!(!is_valid_xml_name(name), "{:?}", name);
+  }
+}
+
+#[test]
+fn test_names_from_the_parser() {
+  let xml = r#"<my-tag data-foo.bar="1" xlink:href='#a' _u="2"/>"#;
+  let (name, attrs) = match ElementIterator::new(xml).next() {
+    Some(XmlElement::EmptyTag { name, attrs }) => (name, attrs),
+    other => panic!("{:?}", other),
+  };
+  assert!(is_valid_xml_name(name));
+  let keys: Vec<_> = TagAttributeIterator::new(attrs).map(|a| a.key).collect();
+  assert_eq!(keys, ["data-foo.bar", "xlink:href", "_u"]);
+  assert!(keys.iter().all(|k| is_valid_xml_name(k)));
+}
+
+#[test]
+fn test_is_reserved_pi_target() {
+  for &target in ["xml", "xml-stylesheet", "xml-model", "XML-foo", "xMl"].iter()
+  {
+    assert!(is_reserved_pi_target(target), "{:?}", target);
+  }
+  for &target in ["php", "", "xm", "x", "my-xml", "é"].iter() {
+    assert!(!is_reserved_pi_target(target), "{:?}", target);
+  }
+
+  let xml = "<?xml-stylesheet href='a.css'?><?php x ?><r/>";
+  let targets: Vec<_> =
+    ElementIterator::new(xml).filter_map(|el| el.pi_target()).collect();
+  assert_eq!(targets, ["xml-stylesheet", "php"]);
+}
+
+#[test]
+fn test_is_nmtoken() {
+  for good in
+    ["a", "1", "1.2.3", "-x", ".hidden", "GL_FLOAT", "xlink:href", "ü", "a·b"]
+  {
+    assert!(is_nmtoken(good), "{:?}", good);
+  }
+  for bad in ["", " ", "a b", "a,b", "a=b", "#x", "a\tb", "a/b", "\"x\""] {
+    assert!(!is_nmtoken(bad), "{:?}", bad);
+  }
+  // every name is a name token, but not the other way around
+  assert!(is_valid_xml_name("type") && is_nmtoken("type"));
+  assert!(!is_valid_xml_name("1type") && is_nmtoken("1type"));
+}
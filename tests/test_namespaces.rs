@@ -0,0 +1,76 @@
+#![cfg(feature = "alloc")]
+
+use magnesium::*;
+
+#[test]
+fn test_nested_prefix_redefinition() {
+  let xml = r#"
+    <root xmlns="urn:default" xmlns:g="urn:graphics/1">
+      <g:enums>
+        <g:inner xmlns:g="urn:graphics/2">
+          <g:enum/>
+        </g:inner>
+        <g:enum/>
+      </g:enums>
+    </root>
+  "#;
+
+  let mut resolver = NamespaceResolver::new();
+  let mut iter = ElementIterator::new(xml).filter_map(skip_empty_text_elements);
+  let mut feed_next = || {
+    let el = iter.next().unwrap();
+    resolver.feed(&el);
+    (el, resolver.clone())
+  };
+
+  let (_root, r) = feed_next();
+  assert_eq!(r.resolve(""), Some("urn:default"));
+  assert_eq!(r.resolve("g"), Some("urn:graphics/1"));
+  assert_eq!(r.resolve("xml"), Some("http://www.w3.org/XML/1998/namespace"));
+  assert_eq!(r.resolve("ferris"), None);
+
+  let (_enums, r) = feed_next();
+  assert_eq!(r.resolve("g"), Some("urn:graphics/1"));
+
+  let (inner, r) = feed_next();
+  assert_eq!(
+    r.resolve_name(inner.unwrap_start_tag().0),
+    Some("urn:graphics/2")
+  );
+  assert_eq!(r.resolve(""), Some("urn:default"));
+
+  let (_enum, r) = feed_next();
+  assert_eq!(r.resolve("g"), Some("urn:graphics/2"));
+
+  let (_inner_end, r) = feed_next();
+  assert_eq!(r.resolve("g"), Some("urn:graphics/1"));
+
+  let (_enum, r) = feed_next();
+  assert_eq!(r.resolve("g"), Some("urn:graphics/1"));
+
+  let (_enums_end, _r) = feed_next();
+  let (_root_end, r) = feed_next();
+  assert_eq!(r.resolve("g"), None);
+  assert_eq!(r.resolve(""), None);
+}
+
+#[test]
+fn test_empty_tag_scope_and_undeclare() {
+  let xml = r#"<a xmlns="urn:a"><b xmlns="" xmlns:x="urn:x"/><c/></a>"#;
+
+  let mut resolver = NamespaceResolver::new();
+  let mut iter = ElementIterator::new(xml);
+
+  resolver.feed(&iter.next().unwrap());
+  assert_eq!(resolver.resolve_name("a"), Some("urn:a"));
+
+  // the empty tag's declarations apply to itself
+  resolver.feed(&iter.next().unwrap());
+  assert_eq!(resolver.resolve_name("b"), None);
+  assert_eq!(resolver.resolve("x"), Some("urn:x"));
+
+  // and then go away
+  resolver.feed(&iter.next().unwrap());
+  assert_eq!(resolver.resolve_name("c"), Some("urn:a"));
+  assert_eq!(resolver.resolve("x"), None);
+}
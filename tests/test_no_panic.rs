@@ -0,0 +1,89 @@
+//! The iterators should never panic, no matter the input.
+//!
+//! There's a `cargo fuzz` target in the `fuzz/` folder for this too, but these
+//! tests run (a smaller version of) the same idea with a normal `cargo test`.
+//! Both use the same `exercise` function, from `shared/exercise.rs`.
+
+use magnesium::*;
+
+include!("shared/exercise.rs");
+
+#[test]
+fn test_no_panic_stress() {
+  // Bits of XML syntax that are likely to confuse a parser when jumbled up.
+  const PIECES: &[&str] = &[
+    "<!--",
+    "-->",
+    "-",
+    "<![CDATA[",
+    "]]>",
+    "<?",
+    "?>",
+    "<",
+    ">",
+    "/",
+    "=",
+    "\"",
+    "'",
+    " ",
+    "\n",
+    "a",
+    "é",
+    "&",
+    ";",
+    "&amp;",
+    "&#x41;",
+    "!",
+    "[",
+    "]",
+    "xml",
+    "x:",
+  ];
+  // A small xorshift, so that the test is the same every time.
+  let mut state = 0x2545_F491_4F6C_DD1D_u64;
+  let mut next = || {
+    state ^= state << 13;
+    state ^= state >> 7;
+    state ^= state << 17;
+    state as usize
+  };
+  let mut xml = String::new();
+  for _ in 0..20_000 {
+    xml.clear();
+    for _ in 0..next() % 24 {
+      xml.push_str(PIECES[next() % PIECES.len()]);
+    }
+    exercise(&xml);
+  }
+}
+
+#[test]
+fn test_no_panic_regressions() {
+  // inputs that have caused panics in the past.
+  let inputs = [
+    // the comment's opening dashes overlapped its closing ones.
+    "<!--->",
+    "<a><!--->",
+    // empty tag names.
+    "<>",
+    "</>",
+    "< />",
+    "<a></>",
+    // multi-byte characters next to the delimiters.
+    "<é/>",
+    "<a>é</a>",
+    "é<",
+    "<a é='é'/>",
+    "<a b='é",
+    // truncated input.
+    "<",
+    "<!",
+    "<![CDATA[",
+    "<?",
+    "<a b=",
+    "<a b='",
+  ];
+  for xml in inputs.iter() {
+    exercise(xml);
+  }
+}
@@ -0,0 +1,149 @@
+#![cfg(feature = "alloc")]
+
+use magnesium::*;
+use std::borrow::Cow;
+
+#[test]
+fn test_coalesce_text_then_cdata() {
+  let xml = "<a>x &amp; y<![CDATA[ & z]]></a>";
+
+  let mut iter = coalesce_text(ElementIterator::new(xml));
+  assert_eq!(
+    iter.next(),
+    Some(CowElement::Other(XmlElement::StartTag { name: "a", attrs: "" }))
+  );
+  assert_eq!(iter.next(), Some(CowElement::Text("x & y & z".into())));
+  assert_eq!(
+    iter.next(),
+    Some(CowElement::Other(XmlElement::EndTag { name: "a" }))
+  );
+  assert_eq!(iter.next(), None);
+}
+
+#[test]
+fn test_coalesce_text_borrows_when_unchanged() {
+  let xml = "<a>plain<b/><![CDATA[raw &amp;]]></a>";
+
+  let mut iter = coalesce_text(ElementIterator::new(xml)).skip(1);
+  assert!(matches!(
+    iter.next(),
+    Some(CowElement::Text(Cow::Borrowed("plain")))
+  ));
+  assert_eq!(
+    iter.next(),
+    Some(CowElement::Other(XmlElement::EmptyTag { name: "b", attrs: "" }))
+  );
+  assert!(matches!(
+    iter.next(),
+    Some(CowElement::Text(Cow::Borrowed("raw &amp;")))
+  ));
+}
+
+#[test]
+fn test_all_text() {
+  let xml = r#"
+    <?xml version="1.0" encoding="UTF-8"?>
+    <registry>
+      <!-- We're gonna pretend that there's a whole file here -->
+      <types>
+        <type>typedef unsigned int <name>GraphicsEnum</name>;</type>
+        <type>#define A (1 &lt;&lt; 2)</type>
+        <type><![CDATA[#define B (A & 3)]]></type>
+      </types>
+    </registry>
+  "#;
+
+  assert_eq!(
+    all_text(xml, true),
+    "typedef unsigned int GraphicsEnum;#define A (1 << 2)#define B (A & 3)"
+  );
+  assert_eq!(all_text("<a> x </a>\n<b/> ", false), " x \n");
+}
+
+#[test]
+fn test_collapse_whitespace() {
+  let el = collapse_whitespace(XmlElement::Text("  a\n\t b  "));
+  assert_eq!(el, Some(CowElement::Text("a b".into())));
+
+  let el = collapse_whitespace(XmlElement::Text("a b"));
+  assert!(matches!(el, Some(CowElement::Text(Cow::Borrowed("a b")))));
+
+  let el = collapse_whitespace(XmlElement::Text("a\nb"));
+  assert!(matches!(el, Some(CowElement::Text(Cow::Owned(_)))));
+  assert_eq!(el, Some(CowElement::Text("a b".into())));
+
+  assert_eq!(collapse_whitespace(XmlElement::Text(" \n\t ")), None);
+  assert_eq!(collapse_whitespace(XmlElement::Text("")), None);
+
+  let tag = XmlElement::EmptyTag { name: "a", attrs: "  x='1'  " };
+  assert_eq!(collapse_whitespace(tag), Some(CowElement::Other(tag)));
+  let cdata = XmlElement::CData("  a  ");
+  assert_eq!(collapse_whitespace(cdata), Some(CowElement::Other(cdata)));
+
+  // after other adapters
+  let xml = "<p>\n  x &lt;\n  y\n</p>";
+  let out: Vec<_> = coalesce_text(ElementIterator::new(xml))
+    .filter_map(collapse_whitespace)
+    .collect();
+  assert_eq!(
+    out,
+    vec![
+      CowElement::Other(XmlElement::StartTag { name: "p", attrs: "" }),
+      CowElement::Text("x < y".into()),
+      CowElement::Other(XmlElement::EndTag { name: "p" }),
+    ]
+  );
+}
+
+#[test]
+fn test_decode_element_text() {
+  assert_eq!(decode_element_text("a&amp;b<![CDATA[c&d]]>"), "a&bc&d");
+  assert_eq!(decode_element_text("<![CDATA[&amp;]]>&amp;"), "&amp;&");
+  assert_eq!(decode_element_text("  x <!-- &amp; --> y  "), "  x  y  ");
+  assert_eq!(decode_element_text("&#x41;<i>&unknown;</i>"), "A&unknown;");
+  assert_eq!(decode_element_text(""), "");
+
+  let xml = "<doc><p>\n  fish &amp; <![CDATA[<chips>]]>\n</p></doc>";
+  let mut iter = ElementIterator::new(xml);
+  let _doc = iter.next();
+  let _p = iter.next();
+  let inner = iter.inner_xml("p").unwrap();
+  assert_eq!(decode_element_text(inner), "\n  fish & <chips>\n");
+}
+
+#[test]
+fn test_normalize_comment_whitespace() {
+  let xml = "<!--\n    Licensed under the\n\tZlib license.\n  --><a/>";
+  let got: Vec<_> = ElementIterator::new(xml)
+    .filter_map(normalize_comment_whitespace)
+    .collect();
+  assert_eq!(
+    got,
+    [
+      CowElement::Comment("Licensed under the Zlib license.".into()),
+      CowElement::Other(XmlElement::EmptyTag { name: "a", attrs: "" }),
+    ]
+  );
+
+  let el = normalize_comment_whitespace(XmlElement::Comment("a b"));
+  assert!(matches!(el, Some(CowElement::Comment(Cow::Borrowed("a b")))));
+  let el = normalize_comment_whitespace(XmlElement::Comment("  "));
+  assert_eq!(el, Some(CowElement::Comment("".into())));
+
+  // text is left alone
+  let text = XmlElement::Text("  a\n  b ");
+  assert_eq!(normalize_comment_whitespace(text), Some(CowElement::Other(text)));
+}
+
+#[test]
+fn test_coalesce_text_unknown_entities() {
+  let xml = "<a>R&D &nbsp;&amp; <![CDATA[x]]></a>";
+  let mut iter = coalesce_text(ElementIterator::new(xml)).skip(1);
+  assert_eq!(iter.next(), Some(CowElement::Text("R&D &nbsp;& x".into())));
+}
+
+#[test]
+fn test_all_text_unknown_entities() {
+  let xml = "<p>R&D<b> &nbsp;&lt;</b> & more</p>";
+  assert_eq!(all_text(xml, false), "R&D &nbsp;< & more");
+}